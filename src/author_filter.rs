@@ -0,0 +1,227 @@
+//! Filtering review threads and reviews by author.
+//!
+//! `--author` keeps only the listed logins, `--exclude-author` drops logins,
+//! and `--no-bots` drops bots. GitHub's GraphQL API reports bot logins
+//! without the `[bot]` suffix the REST API adds, so bots are recognised by
+//! that suffix, by the review bots `vk` knows how to extract, and by the
+//! `bots` list from configuration or `--bot`. Logins compare without regard
+//! to case.
+//!
+//! A thread is judged by the author of its first comment. With
+//! `--any-participant` it is kept when any of its commenters is included and
+//! none of them is excluded.
+
+use crate::cli_args::PrArgs;
+use crate::printer::bots::EXTRACTORS;
+use crate::reviews::PullRequestReview;
+use crate::{ReviewComment, ReviewThread};
+
+/// Suffix GitHub appends to the logins of GitHub Apps.
+const BOT_SUFFIX: &str = "[bot]";
+
+/// Author conditions a thread or review must meet to be shown.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AuthorFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    no_bots: bool,
+    bots: Vec<String>,
+    any_participant: bool,
+}
+
+impl AuthorFilter {
+    /// Build the filter from the `pr` options.
+    #[must_use]
+    pub fn from_args(args: &PrArgs) -> Self {
+        Self {
+            include: args.authors.clone(),
+            exclude: args.exclude_authors.clone(),
+            no_bots: args.no_bots,
+            bots: args.bots.clone(),
+            any_participant: args.any_participant,
+        }
+    }
+
+    /// Whether any author condition is set.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        !(self.include.is_empty() && self.exclude.is_empty() && !self.no_bots)
+    }
+
+    /// Whether an item by `login` passes the filter.
+    ///
+    /// Items whose author is unknown, such as those by deleted accounts,
+    /// pass unless `--author` restricts the logins shown.
+    #[must_use]
+    pub fn allows(&self, login: Option<&str>) -> bool {
+        self.includes(login) && !self.excludes(login)
+    }
+
+    /// Whether `login` meets `--author`.
+    fn includes(&self, login: Option<&str>) -> bool {
+        self.include.is_empty() || login.is_some_and(|login| listed(&self.include, login))
+    }
+
+    /// Whether `login` is dropped by `--exclude-author` or `--no-bots`.
+    fn excludes(&self, login: Option<&str>) -> bool {
+        login.is_some_and(|login| {
+            listed(&self.exclude, login) || (self.no_bots && self.is_bot(login))
+        })
+    }
+
+    /// Whether `login` belongs to a bot.
+    #[must_use]
+    pub fn is_bot(&self, login: &str) -> bool {
+        let suffixed = login
+            .len()
+            .checked_sub(BOT_SUFFIX.len())
+            .and_then(|start| login.get(start..))
+            .is_some_and(|tail| tail.eq_ignore_ascii_case(BOT_SUFFIX));
+        suffixed
+            || EXTRACTORS.iter().any(|e| e.matches(login))
+            || self.bots.iter().any(|b| {
+                let b = b.strip_suffix(BOT_SUFFIX).unwrap_or(b);
+                b.eq_ignore_ascii_case(login)
+            })
+    }
+
+    /// Whether `thread` passes the filter.
+    ///
+    /// With `--any-participant`, one included commenter is enough to keep
+    /// the thread, but one excluded commenter drops it.
+    #[must_use]
+    pub fn keeps_thread(&self, thread: &ReviewThread) -> bool {
+        let mut comments = thread.comments.nodes.iter().map(comment_author);
+        if self.any_participant {
+            (self.include.is_empty() || comments.clone().any(|a| self.includes(a)))
+                && !comments.any(|a| self.excludes(a))
+        } else {
+            comments.next().is_none_or(|a| self.allows(a))
+        }
+    }
+
+    /// Whether `review` passes the filter.
+    #[must_use]
+    pub fn keeps_review(&self, review: &PullRequestReview) -> bool {
+        self.allows(review.author.as_ref().map(|u| u.login.as_str()))
+    }
+
+    /// Drop the threads that fail the filter.
+    #[must_use]
+    pub fn threads(&self, mut threads: Vec<ReviewThread>) -> Vec<ReviewThread> {
+        if self.is_active() {
+            threads.retain(|t| self.keeps_thread(t));
+        }
+        threads
+    }
+
+    /// Drop the reviews that fail the filter.
+    #[must_use]
+    pub fn reviews(&self, mut reviews: Vec<PullRequestReview>) -> Vec<PullRequestReview> {
+        if self.is_active() {
+            reviews.retain(|r| self.keeps_review(r));
+        }
+        reviews
+    }
+}
+
+/// Whether `login` appears in `list`, ignoring case.
+fn listed(list: &[String], login: &str) -> bool {
+    list.iter().any(|l| l.eq_ignore_ascii_case(login))
+}
+
+fn comment_author(comment: &ReviewComment) -> Option<&str> {
+    comment.author.as_ref().map(|u| u.login.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommentConnection, User};
+    use rstest::rstest;
+
+    fn filter(include: &[&str], exclude: &[&str], no_bots: bool) -> AuthorFilter {
+        AuthorFilter {
+            include: include.iter().map(ToString::to_string).collect(),
+            exclude: exclude.iter().map(ToString::to_string).collect(),
+            no_bots,
+            bots: vec!["renovate".into()],
+            any_participant: false,
+        }
+    }
+
+    fn thread(logins: &[&str]) -> ReviewThread {
+        let nodes = logins
+            .iter()
+            .map(|login| ReviewComment {
+                author: Some(User {
+                    login: (*login).into(),
+                }),
+                ..Default::default()
+            })
+            .collect();
+        ReviewThread {
+            comments: CommentConnection {
+                nodes,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case("dependabot[bot]", true)]
+    #[case("coderabbitai", true)]
+    #[case("Renovate", true)]
+    #[case("renovate[bot]", true)]
+    #[case("alice", false)]
+    fn bots_are_recognised(#[case] login: &str, #[case] expected: bool) {
+        assert_eq!(filter(&[], &[], true).is_bot(login), expected);
+    }
+
+    #[rstest]
+    #[case(&[], &[], false, Some("alice"), true)]
+    #[case(&["Alice"], &[], false, Some("alice"), true)]
+    #[case(&["bob"], &[], false, Some("alice"), false)]
+    #[case(&[], &["alice"], false, Some("alice"), false)]
+    #[case(&[], &[], true, Some("coderabbitai"), false)]
+    #[case(&[], &[], true, None, true)]
+    #[case(&["bob"], &[], false, None, false)]
+    fn logins_are_filtered(
+        #[case] include: &[&str],
+        #[case] exclude: &[&str],
+        #[case] no_bots: bool,
+        #[case] login: Option<&str>,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(filter(include, exclude, no_bots).allows(login), expected);
+    }
+
+    #[test]
+    fn threads_match_first_author_or_any_participant() {
+        let threads = || vec![thread(&["coderabbitai", "alice"]), thread(&["bob"])];
+        let mut only_alice = filter(&["alice"], &[], false);
+        assert!(only_alice.threads(threads()).is_empty());
+        only_alice.any_participant = true;
+        assert_eq!(only_alice.threads(threads()).len(), 1);
+        let no_bots = filter(&[], &[], true);
+        assert_eq!(no_bots.threads(threads()).len(), 1);
+    }
+
+    #[rstest]
+    #[case(&[], &["mallory"], 1)]
+    #[case(&["alice"], &["mallory"], 1)]
+    #[case(&["bob"], &["alice"], 0)]
+    #[case(&["bob"], &["mallory"], 1)]
+    #[case(&[], &["carol"], 2)]
+    fn any_participant_exclusions_cover_every_commenter(
+        #[case] include: &[&str],
+        #[case] exclude: &[&str],
+        #[case] kept: usize,
+    ) {
+        let threads = vec![thread(&["alice", "mallory"]), thread(&["bob", "alice"])];
+        let mut filter = filter(include, exclude, false);
+        filter.any_participant = true;
+        assert_eq!(filter.threads(threads).len(), kept);
+    }
+}
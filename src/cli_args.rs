@@ -4,9 +4,10 @@
 //! `main.rs` focused on runtime logic.
 // Imports are referenced by derives; no suppression required.
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use ortho_config::OrthoConfig;
 use serde::{Deserialize, Serialize};
+use termimad::crossterm::style::Color;
 
 /// Global options that apply to every sub-command (e.g. `--repo`).
 #[derive(Parser, Deserialize, Serialize, Default, Debug, OrthoConfig, Clone)]
@@ -27,6 +28,26 @@ pub struct GlobalArgs {
     /// HTTP connection timeout in seconds
     #[arg(long, value_name = "SECS")]
     pub connect_timeout: Option<u64>,
+    /// Use plain ASCII instead of emoji and Unicode symbols in output
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::bool_predicates::not")]
+    pub ascii: bool,
+    /// Write output straight to the terminal instead of through a pager
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::bool_predicates::not")]
+    pub no_pager: bool,
+    /// Emit clickable terminal hyperlinks (defaults to `auto`)
+    #[arg(long, value_enum, value_name = "WHEN")]
+    pub hyperlinks: Option<HyperlinkMode>,
+    /// Colour theme for terminal output (defaults to `dark`)
+    #[arg(long, value_enum, value_name = "THEME")]
+    pub theme: Option<ThemeName>,
+    /// Colours overriding parts of the theme, set in the `[colours]`
+    /// configuration table
+    #[arg(skip)]
+    #[ortho_config(skip_cli)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colours: Option<ThemeColours>,
 }
 
 impl GlobalArgs {
@@ -42,11 +63,260 @@ impl GlobalArgs {
         self.connect_timeout = other
             .connect_timeout
             .or_else(|| self.connect_timeout.take());
+        self.ascii |= other.ascii;
+        self.no_pager |= other.no_pager;
+        self.hyperlinks = other.hyperlinks.or(self.hyperlinks);
+        self.theme = other.theme.or(self.theme);
+        self.colours = other.colours.or_else(|| self.colours.take());
+    }
+}
+
+/// When to emit OSC 8 hyperlinks in terminal output.
+#[derive(ValueEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum HyperlinkMode {
+    /// Link when the terminal appears to support hyperlinks.
+    #[default]
+    Auto,
+    /// Always link, even when output is not a terminal.
+    Always,
+    /// Never link.
+    Never,
+}
+
+/// Built-in colour themes for terminal output.
+#[derive(ValueEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// Muted colours for dark terminal backgrounds.
+    #[default]
+    Dark,
+    /// Darker text and pale diff tints for light terminal backgrounds.
+    Light,
+    /// Bright, saturated colours without background tints.
+    HighContrast,
+    /// Bold and underline only, with no colour at all.
+    NoColour,
+}
+
+/// A terminal colour read from configuration.
+///
+/// Accepts a name such as `red` or `dark_grey`, an ANSI palette index from
+/// 0 to 255, or a `#rrggbb` hex triple.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Colour(pub Color);
+
+impl std::str::FromStr for Colour {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |range: std::ops::Range<usize>| {
+                hex.get(range).and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            return match (hex.len(), channel(0..2), channel(2..4), channel(4..6)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Self(Color::Rgb { r, g, b })),
+                _ => Err(format!("expected `#rrggbb`, got `{s}`")),
+            };
+        }
+        if let Ok(index) = s.parse::<u8>() {
+            return Ok(Self(Color::AnsiValue(index)));
+        }
+        Color::try_from(s)
+            .map(Self)
+            .map_err(|()| format!("unknown colour `{s}`"))
+    }
+}
+
+impl std::fmt::Display for Colour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Color::Rgb { r, g, b } => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+            Color::AnsiValue(index) => write!(f, "{index}"),
+            named => write!(f, "{}", format!("{named:?}").to_lowercase()),
+        }
+    }
+}
+
+impl Serialize for Colour {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Color::AnsiValue(index) => index.serialize(serializer),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Colour {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Index(u8),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Index(index) => Ok(Self(Color::AnsiValue(index))),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// Colours overriding parts of the selected theme.
+///
+/// Unset entries keep the theme's colour. The `no-colour` theme ignores
+/// them all.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeColours {
+    /// Foreground of Markdown headings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<Colour>,
+    /// Foreground of fenced code blocks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Colour>,
+    /// Foreground of inline code spans.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_code: Option<Colour>,
+    /// Background of added diff lines.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added: Option<Colour>,
+    /// Background of removed diff lines.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub removed: Option<Colour>,
+    /// Foreground of author names in comment and review banners.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<Colour>,
+}
+
+/// Output formats supported by the `pr` sub-command.
+#[derive(ValueEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Terminal Markdown rendered with Termimad, framed by banners.
+    #[default]
+    Text,
+    /// Plain GitHub-flavoured Markdown without ANSI escapes, framed by
+    /// banners.
+    Markdown,
+    /// A single versioned JSON document describing the review state.
+    Json,
+    /// One JSON event per line, streamed as each thread is fetched.
+    Ndjson,
+    /// A SARIF 2.1.0 log with one result per review thread.
+    Sarif,
+    /// One `path:line:col: author: message` line per thread for editor
+    /// quickfix lists.
+    Quickfix,
+    /// A self-contained HTML report for sharing outside the terminal.
+    Html,
+}
+
+impl OutputFormat {
+    /// Whether the format is written as one document that `--output` can
+    /// send to a file.
+    #[must_use]
+    pub const fn is_document(self) -> bool {
+        matches!(self, Self::Json | Self::Sarif | Self::Quickfix | Self::Html)
+    }
+
+    /// Whether the format is framed by the `code review` banners.
+    #[must_use]
+    pub const fn uses_banners(self) -> bool {
+        matches!(self, Self::Text | Self::Markdown)
+    }
+
+    /// Resolve the requested format, choosing one when none was given.
+    ///
+    /// Without an explicit format, terminals get styled `text` and pipes or
+    /// files get plain `markdown` so captured output contains no escape
+    /// sequences.
+    #[must_use]
+    pub const fn resolve(requested: Option<Self>, stdout_is_terminal: bool) -> Self {
+        match requested {
+            Some(format) => format,
+            None if stdout_is_terminal => Self::Text,
+            None => Self::Markdown,
+        }
+    }
+}
+
+/// Amount of diff hunk shown around a commented line.
+///
+/// Parsed from a line count or `full`, both on the command line and in
+/// configuration files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffContext {
+    /// Show this many lines before and after the commented line.
+    Lines(usize),
+    /// Show the whole hunk.
+    Full,
+}
+
+impl DiffContext {
+    /// Context used when none is configured.
+    pub const DEFAULT_LINES: usize = 5;
+}
+
+impl Default for DiffContext {
+    fn default() -> Self {
+        Self::Lines(Self::DEFAULT_LINES)
+    }
+}
+
+impl std::str::FromStr for DiffContext {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("full") {
+            return Ok(Self::Full);
+        }
+        s.parse()
+            .map(Self::Lines)
+            .map_err(|_| format!("expected a line count or `full`, got `{s}`"))
+    }
+}
+
+impl std::fmt::Display for DiffContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lines(n) => write!(f, "{n}"),
+            Self::Full => f.write_str("full"),
+        }
+    }
+}
+
+impl Serialize for DiffContext {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Lines(n) => n.serialize(serializer),
+            Self::Full => serializer.serialize_str("full"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DiffContext {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Lines(usize),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Lines(n) => Ok(Self::Lines(n)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
     }
 }
 
 /// Parameters accepted by the `pr` sub-command.
 #[derive(Parser, Deserialize, Serialize, Debug, OrthoConfig, Clone, Default)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "each flag is an independent CLI switch"
+)]
 #[command(name = "pr")]
 #[ortho_config(prefix = "VK")]
 pub struct PrArgs {
@@ -61,10 +331,49 @@ pub struct PrArgs {
     // current branch. The `Option` allows `PrArgs::default()` and config
     // merging to leave it unset.
     pub reference: Option<String>,
-    /// Only show comments for these files
+    /// Only show comments for these files, directories or glob patterns;
+    /// prefix a pattern with `!` to exclude matches
     #[arg(value_name = "FILE", num_args = 0..)]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub files: Vec<String>,
+    /// Only show threads and reviews by this login (repeatable)
+    #[arg(long = "author", value_name = "LOGIN")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    /// Hide threads and reviews by this login (repeatable)
+    #[arg(long = "exclude-author", value_name = "LOGIN")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_authors: Vec<String>,
+    /// Hide threads and reviews by bots: `[bot]` logins, known review bots,
+    /// and the `bots` list
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::bool_predicates::not")]
+    pub no_bots: bool,
+    /// Treat this login as a bot for `--no-bots` (repeatable)
+    #[arg(long = "bot", value_name = "LOGIN")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bots: Vec<String>,
+    /// Apply author filters to every commenter in a thread instead of the
+    /// first
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::bool_predicates::not")]
+    pub any_participant: bool,
+    /// Only show threads where a comment body or diff hunk matches this
+    /// regular expression
+    #[arg(long, value_name = "REGEX")]
+    pub grep: Option<String>,
+    /// Match `--grep` without regard to case
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::bool_predicates::not")]
+    pub ignore_case: bool,
+    /// Only show threads with a comment newer than a duration such as `2d`,
+    /// an ISO 8601 date, or a commit SHA
+    #[arg(long, value_name = "DURATION|DATE|COMMIT")]
+    pub since: Option<String>,
+    /// Only show threads with a comment older than a duration such as `2d`,
+    /// an ISO 8601 date, or a commit SHA
+    #[arg(long, value_name = "DURATION|DATE|COMMIT")]
+    pub until: Option<String>,
     /// Include outdated review threads
     #[arg(short = 'o', long = "show-outdated")]
     // `crate::bool_predicates::not` ensures false CLI defaults cannot override env or config precedence.
@@ -74,6 +383,88 @@ pub struct PrArgs {
         skip_serializing_if = "crate::bool_predicates::not"
     )]
     pub show_outdated: bool,
+    /// Output format (defaults to `text` on a terminal, `markdown` otherwise)
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub format: Option<OutputFormat>,
+    /// Diff lines shown around each commented line, or `full` for the whole
+    /// hunk (defaults to 5)
+    #[arg(long = "context", value_name = "N|full")]
+    pub diff_context: Option<DiffContext>,
+    /// Show lines from the file at the pull request head around each comment
+    /// in place of the diff hunk
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::bool_predicates::not")]
+    pub expand_context: bool,
+    /// Translate each comment's line into the local working tree
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::bool_predicates::not")]
+    pub map_local: bool,
+    /// Classify each unresolved thread by whether its commented lines changed
+    /// in the local working tree
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::bool_predicates::not")]
+    pub local_status: bool,
+    /// Show comment bodies as posted instead of extracting the actionable
+    /// parts of bot comments and collapsing `<details>` blocks
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::bool_predicates::not")]
+    pub raw_body: bool,
+    /// Order threads by file, line, author or age (defaults to the order
+    /// GitHub returns)
+    #[arg(long, value_enum, value_name = "ORDER")]
+    pub sort: Option<ThreadSort>,
+    /// Group threads under a heading per file or author
+    #[arg(long, value_enum, value_name = "KEY")]
+    pub group_by: Option<GroupBy>,
+    /// Show when comments were posted as relative or absolute times
+    /// (defaults to `relative`)
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub timestamps: Option<TimestampStyle>,
+    /// Render the review with a template file, or a template named in the
+    /// configuration directory, in place of `--format`
+    #[arg(long, value_name = "NAME|PATH")]
+    pub template: Option<String>,
+    /// Write `json`, `sarif`, `quickfix`, `html` or template output to this
+    /// file instead of standard output
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<std::path::PathBuf>,
+}
+
+/// How author banners show when a comment or review was posted.
+#[derive(ValueEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimestampStyle {
+    /// Elapsed time, such as `3 days ago`.
+    #[default]
+    Relative,
+    /// Date and time in UTC, such as `on 2025-01-03 14:05 UTC`.
+    Absolute,
+}
+
+/// Orders for review threads in text and Markdown output.
+#[derive(ValueEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThreadSort {
+    /// By file path, keeping GitHub's order within each file.
+    File,
+    /// By file path, then by commented line.
+    Line,
+    /// By the login of the thread's first author.
+    Author,
+    /// Threads with the most recent comment, including replies, first.
+    Newest,
+    /// Earliest started threads first, by their first comment.
+    Oldest,
+}
+
+/// Keys for grouping review threads under headings.
+#[derive(ValueEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GroupBy {
+    /// One group per file path.
+    File,
+    /// One group per login of the thread's first author.
+    Author,
 }
 
 /// Parameters accepted by the `issue` sub-command.
@@ -131,9 +522,38 @@ impl Default for ResolveArgs {
     }
 }
 
+/// Parameters accepted by the `apply` sub-command.
+#[derive(Parser, Deserialize, Serialize, Debug, OrthoConfig, Clone)]
+#[command(name = "apply")]
+#[ortho_config(prefix = "VK")]
+pub struct ApplyArgs {
+    /// Pull request comment URL or number with discussion fragment.
+    #[arg(required = true)]
+    pub reference: String,
+    /// Print the change as a unified patch instead of writing the file
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "crate::bool_predicates::not")]
+    pub dry_run: bool,
+}
+
+#[expect(
+    clippy::derivable_impls,
+    reason = "manual impl clarifies default empty reference"
+)]
+impl Default for ApplyArgs {
+    fn default() -> Self {
+        Self {
+            reference: String::new(),
+            dry_run: false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::GlobalArgs;
+    use super::{Colour, GlobalArgs};
+    use rstest::rstest;
+    use termimad::crossterm::style::Color;
 
     #[test]
     fn merge_prefers_cli_github_token() {
@@ -163,4 +583,45 @@ mod tests {
 
         assert_eq!(config.github_token.as_deref(), Some("config-token"));
     }
+
+    #[rstest]
+    #[case("dark_grey", Ok(Color::DarkGrey))]
+    #[case("Red", Ok(Color::Red))]
+    #[case("208", Ok(Color::AnsiValue(208)))]
+    #[case("#0a0B0c", Ok(Color::Rgb { r: 10, g: 11, b: 12 }))]
+    #[case("#abc", Err(()))]
+    #[case("sky", Err(()))]
+    fn colours_parse(#[case] input: &str, #[case] expected: Result<Color, ()>) {
+        assert_eq!(
+            input.parse::<Colour>().map(|c| c.0).map_err(|_| ()),
+            expected
+        );
+    }
+
+    #[test]
+    fn merge_keeps_ascii_from_either_source() {
+        let mut config = GlobalArgs {
+            ascii: true,
+            ..GlobalArgs::default()
+        };
+        config.merge(GlobalArgs::default());
+        assert!(config.ascii);
+
+        let mut config = GlobalArgs::default();
+        config.merge(GlobalArgs {
+            ascii: true,
+            ..GlobalArgs::default()
+        });
+        assert!(config.ascii);
+    }
+
+    #[test]
+    fn merge_keeps_no_pager_from_either_source() {
+        let mut config = GlobalArgs::default();
+        config.merge(GlobalArgs {
+            no_pager: true,
+            ..GlobalArgs::default()
+        });
+        assert!(config.no_pager);
+    }
 }
@@ -4,27 +4,56 @@
 //! resolution, API client setup, and rendering output to the terminal.
 
 use crate::auth::resolve_github_token;
+use crate::author_filter::AuthorFilter;
 use crate::branch_pr::fetch_pr_for_branch;
-use crate::cli_args::{GlobalArgs, IssueArgs, PrArgs, ResolveArgs};
+use crate::cli_args::{
+    ApplyArgs, DiffContext, GlobalArgs, IssueArgs, OutputFormat, PrArgs, ResolveArgs,
+};
 use crate::environment;
-use crate::printer::{print_reviews, write_thread};
+use crate::file_filter::FileFilter;
+use crate::head_context::{HeadFiles, expand_threads, fetch_head_oid};
+use crate::hyperlink::{LinkTargets, hyperlinks_enabled};
+use crate::pager::Pager;
+use crate::printer::html_report::{HtmlOptions, HtmlReport, write_html_report};
+use crate::printer::json::{
+    NdjsonEvent, PrDocument, PullRequestRef, write_json_document, write_ndjson_event,
+};
+use crate::printer::markdown::MarkdownThreadWriter;
+use crate::printer::quickfix::write_quickfix;
+use crate::printer::sarif::{SarifLog, write_sarif_log};
+use crate::printer::template::{OutputTemplate, templates_dir};
+use crate::printer::{RenderOptions, Renderer};
 use crate::ref_parser::{
     RepoInfo, current_branch, is_fragment_only, parse_fragment_only, parse_issue_reference,
     parse_pr_thread_reference, parse_repo_str, repo_from_fetch_head, repo_from_origin,
+    worktree_prefix, worktree_root,
 };
+use crate::review_threads::ReviewThreadStream;
 use crate::review_threads::thread_for_comment;
 use crate::reviews::{PullRequestReview, fetch_reviews, latest_reviews};
+use crate::suggestion::{CommentedLines, apply_suggestion, suggestions};
 use crate::summary::{
-    print_comments_banner, print_end_banner, print_start_banner, print_summary, summarize_files,
+    FileTally, print_comments_banner, print_end_banner, print_start_banner, print_summary,
+    summarize_files, summarize_files_in_order,
 };
+use crate::theme::Theme;
+use crate::thread_order::{ThreadGroup, group_threads, sort_threads};
+use crate::thread_search::ThreadSearch;
+use crate::time_filter::{TimeFilter, TimeSpec};
+use crate::worktree::{LocalCheckout, classify_threads, map_threads};
 use crate::{
     FetchOptions, GraphQLClient, ReviewThread, VkError, fetch_issue,
     fetch_review_threads_with_options, filter_threads_by_files, resolve,
 };
+use chrono::Utc;
+use diffy::DiffOptions;
 use std::any::Any;
-use std::io::{ErrorKind, Write};
-use termimad::MadSkin;
+use std::borrow::Cow;
+use std::io::{ErrorKind, IsTerminal, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
 use tracing::{debug, error, warn};
+use vk::icons::Icons;
 
 #[cfg(feature = "unstable-rest-resolve")]
 use std::time::Duration;
@@ -39,13 +68,28 @@ struct PrContext {
     number: u64,
     comment_id: Option<u64>,
     client: GraphQLClient,
+    format: OutputFormat,
+    /// File patterns a thread's path must match.
+    files: FileFilter,
+    /// Author conditions applied to threads and reviews.
+    authors: AuthorFilter,
+    /// Window of time a thread's comments or a review must fall in.
+    times: TimeFilter,
+    /// Pattern a thread's comments or diff must contain.
+    search: ThreadSearch,
+    /// Whether standard output was a terminal before any pager took it over.
+    stdout_is_terminal: bool,
+    /// Pager receiving standard output; closed when the context is dropped.
+    _pager: Option<Pager>,
 }
 
 /// Print a review thread to stdout.
 ///
-/// This simply calls [`write_thread`] with a locked `stdout` handle.
-fn print_thread(skin: &MadSkin, thread: &ReviewThread) -> Result<(), VkError> {
-    write_thread(std::io::stdout().lock(), skin, thread).map_err(map_printer_error)
+/// This simply calls [`Renderer::write_thread`] with a locked `stdout` handle.
+fn print_thread(renderer: &mut Renderer, thread: &ReviewThread) -> Result<(), VkError> {
+    renderer
+        .write_thread(std::io::stdout().lock(), thread)
+        .map_err(map_printer_error)
 }
 
 /// Create a [`GraphQLClient`], falling back to no transcript on failure.
@@ -107,12 +151,20 @@ where
     false
 }
 
-fn warn_on_missing_token_and_locale(token: &str) {
+/// Warn about an anonymous token or a non-UTF-8 locale.
+///
+/// `uses_icons` says whether the output shows icons in the terminal, which
+/// the locale switches to ASCII.
+fn warn_on_missing_token_and_locale(token: &str, uses_icons: bool) {
     if token.is_empty() {
         warn!("GitHub token not set, using anonymous API access");
     }
     if !locale_is_utf8() {
-        warn!("terminal locale is not UTF-8; emojis may not render correctly");
+        if uses_icons {
+            warn!("terminal locale is not UTF-8; using ASCII output");
+        } else {
+            warn!("terminal locale is not UTF-8; emojis may not render correctly");
+        }
     }
 }
 
@@ -140,11 +192,11 @@ where
     false
 }
 
-fn print_reviews_block(skin: &MadSkin, reviews: Vec<PullRequestReview>) -> bool {
+fn print_reviews_block(renderer: &Renderer, reviews: Vec<PullRequestReview>) -> bool {
     let latest = latest_reviews(reviews);
     let stdout = std::io::stdout();
     let mut handle = stdout.lock();
-    if let Err(e) = print_reviews(&mut handle, skin, &latest) {
+    if let Err(e) = renderer.write_reviews(&mut handle, &latest) {
         if caused_by_broken_pipe(&e) {
             return true;
         }
@@ -153,9 +205,9 @@ fn print_reviews_block(skin: &MadSkin, reviews: Vec<PullRequestReview>) -> bool
     false
 }
 
-fn print_threads_block(skin: &MadSkin, threads: Vec<ReviewThread>) -> bool {
+fn print_threads_block(renderer: &mut Renderer, threads: Vec<ReviewThread>) -> bool {
     for thread in threads {
-        if let Err(e) = print_thread(skin, &thread) {
+        if let Err(e) = print_thread(renderer, &thread) {
             if matches!(e, VkError::Io(ref inner) if is_broken_pipe_kind(inner.kind())) {
                 return true;
             }
@@ -165,6 +217,22 @@ fn print_threads_block(skin: &MadSkin, threads: Vec<ReviewThread>) -> bool {
     false
 }
 
+/// Print each group's heading followed by its threads.
+fn print_groups_block(renderer: &mut Renderer, groups: Vec<ThreadGroup>) -> bool {
+    for group in groups {
+        if let Err(e) = renderer.write_group_heading(std::io::stdout().lock(), &group) {
+            if caused_by_broken_pipe(&e) {
+                return true;
+            }
+            error!("error printing group heading: {e}");
+        }
+        if print_threads_block(renderer, group.threads) {
+            return true;
+        }
+    }
+    false
+}
+
 /// Context for branch-based PR auto-detection.
 struct BranchContext {
     /// The target repository (where the PR is opened against).
@@ -262,41 +330,69 @@ async fn resolve_pr_reference(
     }
 }
 
-/// Prepare PR context, validate environment and print the start banner.
+/// Prepare PR context, validate environment, start the pager and print the
+/// start banner.
 ///
-/// Returns `Ok(None)` when standard output is closed before printing.
+/// Templates draw their own banners, so none is printed when `--template` is
+/// set. Returns `Ok(None)` when standard output is closed before printing.
 async fn setup_pr_output(
     args: &PrArgs,
     global: &GlobalArgs,
     cli_token: Option<&str>,
 ) -> Result<Option<PrContext>, VkError> {
+    // File patterns are relative to the current directory, while GitHub
+    // reports paths from the repository root.
+    let files = FileFilter::new(&args.files, &worktree_prefix().unwrap_or_default())?;
+    let search = ThreadSearch::from_args(args)?;
+    let since = args.since.as_deref().map(TimeSpec::parse).transpose()?;
+    let until = args.until.as_deref().map(TimeSpec::parse).transpose()?;
     let token = resolve_github_token(cli_token, global.github_token.as_deref());
-    warn_on_missing_token_and_locale(&token);
-    if handle_banner(print_start_banner, "start") {
+    let stdout_is_terminal = std::io::stdout().is_terminal();
+    let format = OutputFormat::resolve(args.format, stdout_is_terminal);
+    let templated = args.template.is_some();
+    warn_on_missing_token_and_locale(&token, templated || format.uses_banners());
+    let pager = start_pager(
+        global,
+        stdout_is_terminal && (templated || format.uses_banners()),
+    );
+    if !templated && format.uses_banners() && handle_banner(print_start_banner, "start") {
         return Ok(None);
     }
     let client = build_graphql_client(&token, global.transcript.as_ref())?;
     let (repo, number, comment) =
         resolve_pr_reference(args.reference.as_deref(), global.repo.as_deref(), &client).await?;
+    let times =
+        TimeFilter::resolve(since.as_ref(), until.as_ref(), &client, &repo, Utc::now()).await?;
     Ok(Some(PrContext {
         repo,
         number,
         comment_id: comment,
         client,
+        format,
+        files,
+        authors: AuthorFilter::from_args(args),
+        times,
+        search,
+        stdout_is_terminal,
+        _pager: pager,
     }))
 }
 
+/// Page human-readable output on a terminal unless `--no-pager` was given.
+fn start_pager(global: &GlobalArgs, page: bool) -> Option<Pager> {
+    if global.no_pager {
+        return None;
+    }
+    Pager::start(page)
+}
+
 /// Print an appropriate message when no threads match and append the end banner.
 #[expect(
     clippy::unnecessary_wraps,
     reason = "returns Result for interface symmetry"
 )]
-fn handle_empty_threads(files: &[String], comment: Option<u64>) -> Result<(), VkError> {
-    let msg = match (comment.is_some(), files.is_empty()) {
-        (true, _) => "No unresolved comments in the requested discussion.",
-        (false, true) => "No unresolved comments.",
-        (false, false) => "No unresolved comments for the specified files.",
-    };
+fn handle_empty_threads(ctx: &PrContext) -> Result<(), VkError> {
+    let msg = empty_threads_message(ctx);
     if let Err(e) = writeln!(std::io::stdout().lock(), "{msg}") {
         if is_broken_pipe_kind(e.kind()) {
             return Ok(());
@@ -309,13 +405,58 @@ fn handle_empty_threads(files: &[String], comment: Option<u64>) -> Result<(), Vk
     Ok(())
 }
 
+/// Explain why no thread was selected, naming the filters in force.
+fn empty_threads_message(ctx: &PrContext) -> Cow<'static, str> {
+    if ctx.comment_id.is_some() {
+        return "No unresolved comments in the requested discussion.".into();
+    }
+    let filters: Vec<&str> = [
+        (ctx.files.is_active(), "file"),
+        (ctx.authors.is_active(), "author"),
+        (ctx.times.is_active(), "time"),
+        (ctx.search.is_active(), "search"),
+    ]
+    .into_iter()
+    .filter_map(|(active, name)| active.then_some(name))
+    .collect();
+    match filters.as_slice() {
+        [] => "No unresolved comments.".into(),
+        ["file"] => "No unresolved comments for the specified files.".into(),
+        ["author"] => "No unresolved comments from the specified authors.".into(),
+        ["time"] => "No unresolved comments in the requested time range.".into(),
+        ["search"] => "No unresolved comments match the search.".into(),
+        [init @ .., last] => format!(
+            "No unresolved comments match the {} and {last} filters.",
+            init.join(", ")
+        )
+        .into(),
+    }
+}
+
 /// Render the summary, reviews and threads, then print the closing banner.
-fn generate_pr_output(threads: Vec<ReviewThread>, reviews: Vec<PullRequestReview>) {
-    let summary = summarize_files(&threads);
+///
+/// Threads are sorted and grouped as `args` asks. When either is requested,
+/// the summary lists files in the order they appear in the body.
+fn generate_pr_output(
+    mut renderer: Renderer,
+    mut threads: Vec<ReviewThread>,
+    reviews: Vec<PullRequestReview>,
+    args: &PrArgs,
+) {
+    if let Some(sort) = args.sort {
+        sort_threads(&mut threads, sort);
+    }
+    let groups = args
+        .group_by
+        .map(|by| group_threads(std::mem::take(&mut threads), by));
+    let summary = match (&groups, args.sort) {
+        (Some(groups), _) => summarize_files_in_order(groups.iter().flat_map(|g| &g.threads)),
+        (None, Some(_)) => summarize_files_in_order(&threads),
+        (None, None) => summarize_files(&threads),
+    };
     print_summary(&summary);
 
-    let skin = MadSkin::default();
-    if print_reviews_block(&skin, reviews) {
+    if print_reviews_block(&renderer, reviews) {
         return;
     }
 
@@ -325,13 +466,420 @@ fn generate_pr_output(threads: Vec<ReviewThread>, reviews: Vec<PullRequestReview
         return;
     }
 
-    if print_threads_block(&skin, threads) {
+    let stopped = match groups {
+        Some(groups) => print_groups_block(&mut renderer, groups),
+        None => print_threads_block(&mut renderer, threads),
+    };
+    if stopped {
         return;
     }
 
     let _ = handle_banner(print_end_banner, "end");
 }
 
+/// Narrow fetched threads to the requested discussion, or to the file,
+/// author, time and search filters.
+///
+/// A discussion comment takes precedence; the other filters are ignored when
+/// it is present.
+fn select_threads(threads: Vec<ReviewThread>, ctx: &PrContext) -> Vec<ReviewThread> {
+    if let Some(comment_id) = ctx.comment_id {
+        thread_for_comment(threads, comment_id)
+            .into_iter()
+            .collect()
+    } else {
+        let threads = ctx
+            .authors
+            .threads(filter_threads_by_files(threads, &ctx.files));
+        ctx.search.threads(ctx.times.threads(threads))
+    }
+}
+
+/// Fetch every thread matching `options`, then apply discussion and file
+/// filters.
+async fn fetch_selected_threads(
+    ctx: &PrContext,
+    options: FetchOptions,
+) -> Result<Vec<ReviewThread>, VkError> {
+    fetch_review_threads_with_options(&ctx.client, &ctx.repo, ctx.number, options)
+        .await
+        .map(|threads| select_threads(threads, ctx))
+}
+
+/// Fetch the pull request's reviews that pass the author and time filters.
+async fn fetch_selected_reviews(ctx: &PrContext) -> Result<Vec<PullRequestReview>, VkError> {
+    fetch_reviews(&ctx.client, &ctx.repo, ctx.number)
+        .await
+        .map(|reviews| ctx.times.reviews(ctx.authors.reviews(reviews)))
+}
+
+/// Attach head-commit context, local line mappings, and local status when
+/// `--expand-context`, `--map-local`, or `--local-status` is set.
+///
+/// `head` is the pull request head commit, which context and mappings need.
+async fn enrich_threads(
+    ctx: &PrContext,
+    args: &PrArgs,
+    head: Option<&str>,
+    threads: &mut [ReviewThread],
+) -> Result<(), VkError> {
+    if threads.is_empty() {
+        return Ok(());
+    }
+    let mut enricher = ThreadEnricher::new(ctx, args, head);
+    enricher.enrich(threads).await?;
+    enricher.finish();
+    Ok(())
+}
+
+/// Adds what `--expand-context`, `--map-local`, and `--local-status` ask
+/// for to threads, one batch at a time.
+///
+/// Context and mappings both use the pull request head commit fetched by
+/// [`fetch_head_for`]. Head files and local file versions are cached across
+/// batches, so streaming threads one by one costs no more than enriching
+/// them together.
+struct ThreadEnricher<'a> {
+    /// Working tree to classify threads against.
+    status_root: Option<PathBuf>,
+    /// Checkout to map comment lines into, with the head commit it uses.
+    checkout: Option<(LocalCheckout, String)>,
+    /// Files at the head commit for `--expand-context`.
+    head_files: Option<HeadFiles<'a>>,
+    context: DiffContext,
+    /// Comments whose head file was not available locally.
+    unavailable: usize,
+}
+
+impl<'a> ThreadEnricher<'a> {
+    /// Prepare what the options in `args` need, using `head` for context
+    /// and mappings.
+    ///
+    /// Mapping and classifying are best effort: outside a Git checkout they
+    /// are skipped with a warning.
+    fn new(ctx: &'a PrContext, args: &PrArgs, head: Option<&str>) -> Self {
+        let status_root = if args.local_status {
+            let root = worktree_root();
+            if root.is_none() {
+                warn!("--local-status requires a Git working tree; skipping");
+            }
+            root
+        } else {
+            None
+        };
+        let checkout = match (head, args.map_local) {
+            (Some(oid), true) => {
+                let root = worktree_root();
+                if root.is_none() {
+                    warn!("--map-local requires a Git working tree; showing pull request lines");
+                }
+                root.map(|root| (LocalCheckout::new(root, oid.to_owned()), oid.to_owned()))
+            }
+            _ => None,
+        };
+        let head_files = head
+            .filter(|_| args.expand_context)
+            .map(|oid| HeadFiles::new(&ctx.client, &ctx.repo, oid.to_owned()));
+        Self {
+            status_root,
+            checkout,
+            head_files,
+            context: args.diff_context.unwrap_or_default(),
+            unavailable: 0,
+        }
+    }
+
+    /// Enrich `threads` in place.
+    async fn enrich(&mut self, threads: &mut [ReviewThread]) -> Result<(), VkError> {
+        if let Some(root) = &self.status_root {
+            classify_threads(root, threads);
+        }
+        if let Some((checkout, _)) = &mut self.checkout {
+            self.unavailable += map_threads(checkout, threads);
+        }
+        if let Some(files) = &mut self.head_files {
+            expand_threads(files, threads, self.context).await?;
+        }
+        Ok(())
+    }
+
+    /// Warn about comments that could not be mapped.
+    fn finish(self) {
+        if let Some((_, oid)) = self.checkout
+            && self.unavailable > 0
+        {
+            warn!(
+                "{} comment(s) could not be mapped; check that commit {oid} is \
+                 available locally (run `git fetch`) and the files are readable",
+                self.unavailable
+            );
+        }
+    }
+}
+
+/// Whether terminal output should carry hyperlinks.
+fn wants_links(ctx: &PrContext, global: &GlobalArgs) -> bool {
+    let mode = global.hyperlinks.unwrap_or_default();
+    ctx.format == OutputFormat::Text && hyperlinks_enabled(mode, ctx.stdout_is_terminal)
+}
+
+/// Fetch the pull request head commit once for everything that needs it.
+///
+/// `--expand-context` and `--map-local` cannot work without it, so a failure
+/// is an error. Hyperlinks (`links`) are best effort: if the request fails,
+/// paths are left unlinked.
+async fn fetch_head_for(
+    ctx: &PrContext,
+    args: &PrArgs,
+    links: bool,
+) -> Result<Option<String>, VkError> {
+    if args.expand_context || args.map_local {
+        return fetch_head_oid(&ctx.client, &ctx.repo, ctx.number)
+            .await
+            .map(Some);
+    }
+    if !links {
+        return Ok(None);
+    }
+    match fetch_head_oid(&ctx.client, &ctx.repo, ctx.number).await {
+        Ok(oid) => Ok(Some(oid)),
+        Err(e) => {
+            warn!("could not fetch pull request head for file links: {e}");
+            Ok(None)
+        }
+    }
+}
+
+/// Work out hyperlink targets when terminal hyperlinks are enabled.
+///
+/// File paths link to the pull request `head`, when it could be fetched.
+/// With `--map-local`, mapped comments link to the local checkout instead.
+fn link_targets(
+    ctx: &PrContext,
+    args: &PrArgs,
+    links: bool,
+    head: Option<&str>,
+) -> Option<LinkTargets> {
+    if !links {
+        return None;
+    }
+    let targets = head.map_or_else(LinkTargets::default, |oid| {
+        LinkTargets::at_head(&ctx.repo.owner, &ctx.repo.name, oid)
+    });
+    let local_root = if args.map_local {
+        worktree_root()
+    } else {
+        None
+    };
+    Some(targets.with_local_root(local_root))
+}
+
+/// Load the `--template` named by `spec` with the display options in `args`.
+///
+/// Names without a path are looked up in the configuration directory.
+fn load_template(
+    spec: &str,
+    args: &PrArgs,
+    global: &GlobalArgs,
+) -> Result<OutputTemplate, VkError> {
+    let dir = templates_dir(|name| environment::var(name).ok());
+    Ok(OutputTemplate::load(spec, dir.as_deref())?
+        .with_context(args.diff_context.unwrap_or_default())
+        .with_raw_body(args.raw_body)
+        .with_icons(output_icons(global))
+        .with_timestamps(args.timestamps.unwrap_or_default()))
+}
+
+/// Render the review state with a user-defined template.
+///
+/// Threads are sorted as `args` asks; templates do their own grouping, so
+/// `--group-by` is ignored. Reviews are fetched even when no thread matches
+/// so the template decides what an empty review looks like.
+async fn print_template_output(
+    ctx: &PrContext,
+    args: &PrArgs,
+    options: FetchOptions,
+    head: Option<&str>,
+    template: &OutputTemplate,
+) -> Result<(), VkError> {
+    if args.group_by.is_some() {
+        warn!("--group-by is ignored with --template");
+    }
+    let mut threads = fetch_selected_threads(ctx, options).await?;
+    enrich_threads(ctx, args, head, &mut threads).await?;
+    if let Some(sort) = args.sort {
+        sort_threads(&mut threads, sort);
+    }
+    let summary = match args.sort {
+        Some(_) => summarize_files_in_order(&threads),
+        None => summarize_files(&threads),
+    };
+    let reviews = latest_reviews(fetch_selected_reviews(ctx).await?);
+    let text = template.render(&ctx.repo, ctx.number, &summary, &reviews, &threads)?;
+    write_document(args.output.as_deref(), |out| {
+        out.write_all(text.as_bytes()).map_err(anyhow::Error::from)
+    })
+}
+
+/// Print the review state as a single JSON document.
+fn print_json_output(
+    repo: &RepoInfo,
+    number: u64,
+    threads: &[ReviewThread],
+    reviews: Vec<PullRequestReview>,
+    output: Option<&Path>,
+) -> Result<(), VkError> {
+    let summary = summarize_files(threads);
+    let reviews = latest_reviews(reviews);
+    let document = PrDocument::new(
+        PullRequestRef::new(repo, number),
+        &summary,
+        &reviews,
+        threads,
+    );
+    write_document(output, |out| write_json_document(out, &document))
+}
+
+/// Print the selected threads as a SARIF log.
+fn print_sarif_output(threads: &[ReviewThread], output: Option<&Path>) -> Result<(), VkError> {
+    write_document(output, |out| write_sarif_log(out, &SarifLog::new(threads)))
+}
+
+/// Write the review state as a self-contained HTML report.
+fn print_html_output(
+    ctx: &PrContext,
+    args: &PrArgs,
+    threads: &[ReviewThread],
+    reviews: Vec<PullRequestReview>,
+    icons: Icons,
+) -> Result<(), VkError> {
+    let summary = summarize_files(threads);
+    let reviews = latest_reviews(reviews);
+    let report = HtmlReport {
+        repo: &ctx.repo,
+        number: ctx.number,
+        summary: &summary,
+        reviews: &reviews,
+        threads,
+    };
+    let options = HtmlOptions {
+        context: args.diff_context.unwrap_or_default(),
+        raw_body: args.raw_body,
+        icons,
+        timestamps: args.timestamps.unwrap_or_default(),
+    };
+    write_document(args.output.as_deref(), |out| {
+        write_html_report(out, &report, options)
+    })
+}
+
+/// Write a single-document format to the `--output` file, or to standard
+/// output when none was given.
+fn write_document(
+    output: Option<&Path>,
+    write: impl FnOnce(&mut dyn Write) -> anyhow::Result<()>,
+) -> Result<(), VkError> {
+    let Some(path) = output else {
+        return finish_document(write(&mut std::io::stdout().lock()));
+    };
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write(&mut file)
+        .and_then(|()| file.flush().map_err(anyhow::Error::from))
+        .map_err(map_printer_error)
+}
+
+/// Map the outcome of writing a machine-readable document.
+///
+/// A closed pipe is treated as success; other failures are reported because
+/// consumers parse the output.
+fn finish_document(result: anyhow::Result<()>) -> Result<(), VkError> {
+    match result {
+        Err(e) if caused_by_broken_pipe(&e) => Ok(()),
+        result => result.map_err(map_printer_error),
+    }
+}
+
+/// Write one NDJSON event to stdout.
+///
+/// Breaks with `Ok(())` once stdout has been closed, or with the mapped error
+/// for any other write failure, so callers can stop producing events.
+fn emit_ndjson(event: &NdjsonEvent<'_>) -> ControlFlow<Result<(), VkError>> {
+    match write_ndjson_event(std::io::stdout().lock(), event) {
+        Ok(()) => ControlFlow::Continue(()),
+        Err(e) if caused_by_broken_pipe(&e) => ControlFlow::Break(Ok(())),
+        Err(e) => ControlFlow::Break(Err(map_printer_error(e))),
+    }
+}
+
+/// Stream each selected thread as it finishes comment pagination, enriched
+/// as `args` asks.
+///
+/// Returns the tally of streamed comments and the thread count, or the
+/// outcome that stopped the stream early.
+async fn stream_ndjson_threads(
+    ctx: &PrContext,
+    args: &PrArgs,
+    options: FetchOptions,
+    head: Option<&str>,
+) -> Result<ControlFlow<Result<(), VkError>, (FileTally, usize)>, VkError> {
+    let mut tally = FileTally::default();
+    let mut count = 0;
+    let mut enricher = ThreadEnricher::new(ctx, args, head);
+    let mut stream = ReviewThreadStream::new(&ctx.client, &ctx.repo, ctx.number, options)?;
+    while let Some(thread) = stream.next_thread().await? {
+        let mut selected = select_threads(vec![thread], ctx);
+        enricher.enrich(&mut selected).await?;
+        for thread in &selected {
+            tally.add_thread(thread);
+            count += 1;
+            if let ControlFlow::Break(outcome) = emit_ndjson(&NdjsonEvent::Thread(thread)) {
+                return Ok(ControlFlow::Break(outcome));
+            }
+        }
+    }
+    enricher.finish();
+    Ok(ControlFlow::Continue((tally, count)))
+}
+
+/// Print the review state as NDJSON events without buffering threads.
+///
+/// The start event identifies the pull request before reviews or threads are
+/// fetched. Reviews come next so their events precede the threads; the
+/// summary and end events close the stream.
+async fn stream_ndjson_output(
+    ctx: &PrContext,
+    args: &PrArgs,
+    options: FetchOptions,
+    head: Option<&str>,
+) -> Result<(), VkError> {
+    let pull_request = PullRequestRef::new(&ctx.repo, ctx.number);
+    if let ControlFlow::Break(outcome) = emit_ndjson(&NdjsonEvent::start(pull_request)) {
+        return outcome;
+    }
+    let reviews = latest_reviews(fetch_selected_reviews(ctx).await?);
+    for review in &reviews {
+        if let ControlFlow::Break(outcome) = emit_ndjson(&NdjsonEvent::Review(review)) {
+            return outcome;
+        }
+    }
+    let (tally, threads) = match stream_ndjson_threads(ctx, args, options, head).await? {
+        ControlFlow::Continue(streamed) => streamed,
+        ControlFlow::Break(outcome) => return outcome,
+    };
+    let summary = tally.into_summary();
+    if let ControlFlow::Break(outcome) = emit_ndjson(&NdjsonEvent::summary(&summary)) {
+        return outcome;
+    }
+    let end = NdjsonEvent::End {
+        reviews: reviews.len(),
+        threads,
+    };
+    match emit_ndjson(&end) {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(outcome) => outcome,
+    }
+}
+
 /// Run the `pr` command and print pull request review threads.
 ///
 /// Parameters:
@@ -343,52 +891,107 @@ fn generate_pr_output(threads: Vec<ReviewThread>, reviews: Vec<PullRequestReview
 /// Returns `Ok(())` on success or a `VkError` when the reference is invalid or
 /// the API request fails. When a discussion comment ID is present, resolved
 /// threads are included; otherwise unresolved threads are filtered by file.
+/// `args.format` selects between the banner-framed terminal or Markdown
+/// output, a JSON document, a stream of NDJSON events, a SARIF log, and
+/// quickfix lines; `args.template` replaces the format with a user-defined
+/// template.
 pub async fn run_pr(
     args: PrArgs,
     global: &GlobalArgs,
     cli_token: Option<&str>,
 ) -> Result<(), VkError> {
-    let Some(PrContext {
-        repo,
-        number,
-        comment_id: comment,
-        client,
-    }) = setup_pr_output(&args, global, cli_token).await?
-    else {
+    // Load the template before any request so a typo fails fast.
+    let template = args
+        .template
+        .as_deref()
+        .map(|spec| load_template(spec, &args, global))
+        .transpose()?;
+    let Some(ctx) = setup_pr_output(&args, global, cli_token).await? else {
         return Ok(());
     };
 
     // When a discussion fragment is given, fetch ALL threads (resolved + unresolved)
     // and filter to the specific thread. Otherwise, fetch only unresolved threads
     // and apply file filters.
-    let include_resolved = comment.is_some();
-    let threads = fetch_review_threads_with_options(
-        &client,
-        &repo,
-        number,
-        FetchOptions {
-            include_resolved,
-            include_outdated: args.show_outdated,
+    let options = FetchOptions {
+        include_resolved: ctx.comment_id.is_some(),
+        include_outdated: args.show_outdated,
+    };
+    let links = template.is_none() && wants_links(&ctx, global);
+    let head = fetch_head_for(&ctx, &args, links).await?;
+    if let Some(template) = template {
+        return print_template_output(&ctx, &args, options, head.as_deref(), &template).await;
+    }
+    if args.output.is_some() && !ctx.format.is_document() {
+        warn!("--output only applies to json, sarif, quickfix and html output; ignoring it");
+    }
+
+    let diff_context = args.diff_context.unwrap_or_default();
+    let icons = output_icons(global);
+    let theme = Theme::from_args(global);
+    let renderer = match ctx.format {
+        OutputFormat::Text => Renderer::Terminal {
+            skin: Box::new(theme.skin(icons)),
+            options: RenderOptions {
+                highlight: ctx.stdout_is_terminal && theme.colours_diffs(),
+                context: diff_context,
+                raw_body: args.raw_body,
+                icons,
+                palette: theme.palette(),
+                links: link_targets(&ctx, &args, links, head.as_deref()),
+                timestamps: args.timestamps.unwrap_or_default(),
+                matches: ctx.search.pattern().cloned(),
+            },
         },
-    )
-    .await
-    .map(|threads| {
-        if let Some(comment_id) = comment {
-            thread_for_comment(threads, comment_id)
-                .into_iter()
-                .collect()
-        } else {
-            filter_threads_by_files(threads, &args.files)
+        OutputFormat::Markdown => Renderer::Markdown(
+            MarkdownThreadWriter::new(diff_context)
+                .with_raw_body(args.raw_body)
+                .with_icons(icons)
+                .with_timestamps(args.timestamps.unwrap_or_default())
+                .with_matches(ctx.search.pattern().cloned()),
+        ),
+        OutputFormat::Json => {
+            let mut threads = fetch_selected_threads(&ctx, options).await?;
+            enrich_threads(&ctx, &args, head.as_deref(), &mut threads).await?;
+            // Machine consumers always receive the full document, so reviews
+            // are fetched even when no thread matches.
+            let reviews = fetch_selected_reviews(&ctx).await?;
+            return print_json_output(
+                &ctx.repo,
+                ctx.number,
+                &threads,
+                reviews,
+                args.output.as_deref(),
+            );
         }
-    })?;
-
+        OutputFormat::Ndjson => {
+            return stream_ndjson_output(&ctx, &args, options, head.as_deref()).await;
+        }
+        OutputFormat::Sarif => {
+            let mut threads = fetch_selected_threads(&ctx, options).await?;
+            enrich_threads(&ctx, &args, head.as_deref(), &mut threads).await?;
+            return print_sarif_output(&threads, args.output.as_deref());
+        }
+        OutputFormat::Quickfix => {
+            let mut threads = fetch_selected_threads(&ctx, options).await?;
+            enrich_threads(&ctx, &args, head.as_deref(), &mut threads).await?;
+            return write_document(args.output.as_deref(), |out| write_quickfix(out, &threads));
+        }
+        OutputFormat::Html => {
+            let mut threads = fetch_selected_threads(&ctx, options).await?;
+            enrich_threads(&ctx, &args, head.as_deref(), &mut threads).await?;
+            let reviews = fetch_selected_reviews(&ctx).await?;
+            return print_html_output(&ctx, &args, &threads, reviews, icons);
+        }
+    };
+    let mut threads = fetch_selected_threads(&ctx, options).await?;
     if threads.is_empty() {
-        handle_empty_threads(&args.files, comment)?;
+        handle_empty_threads(&ctx)?;
         return Ok(());
     }
-
-    let reviews = fetch_reviews(&client, &repo, number).await?;
-    generate_pr_output(threads, reviews);
+    enrich_threads(&ctx, &args, head.as_deref(), &mut threads).await?;
+    let reviews = fetch_selected_reviews(&ctx).await?;
+    generate_pr_output(renderer, threads, reviews, &args);
     Ok(())
 }
 
@@ -410,12 +1013,13 @@ pub async fn run_issue(
     let reference = args.reference.as_deref().ok_or(VkError::InvalidRef)?;
     let (repo, number) = parse_issue_reference(reference, global.repo.as_deref())?;
     let token = resolve_github_token(cli_token, global.github_token.as_deref());
-    warn_on_missing_token_and_locale(&token);
+    warn_on_missing_token_and_locale(&token, true);
 
     let client = build_graphql_client(&token, global.transcript.as_ref())?;
     let issue = fetch_issue(&client, &repo, number).await?;
 
-    let skin = MadSkin::default();
+    let skin = Theme::from_args(global).skin(output_icons(global));
+    let _pager = start_pager(global, std::io::stdout().is_terminal());
     let stdout = std::io::stdout();
     let mut handle = stdout.lock();
     if handle_print_result(
@@ -497,6 +1101,81 @@ pub async fn run_resolve(
     }
 }
 
+/// Run the `apply` command, writing a comment's suggested change into the
+/// local checkout.
+///
+/// Parameters:
+/// - `args`: the comment reference and whether to print a patch instead.
+/// - `global`: global CLI options, including repository defaults and transcript
+///   output.
+/// - `cli_token`: optional authentication token from the CLI.
+///
+/// Returns a `VkError` when the comment cannot be found, carries no
+/// suggestion, or its commented lines no longer match the local file.
+pub async fn run_apply(
+    args: ApplyArgs,
+    global: &GlobalArgs,
+    cli_token: Option<&str>,
+) -> Result<(), VkError> {
+    let (repo, number, comment) =
+        parse_pr_thread_reference(&args.reference, global.repo.as_deref())?;
+    let comment_id = comment.ok_or(VkError::InvalidRef)?;
+    let token = resolve_github_token(cli_token, global.github_token.as_deref());
+    warn_on_missing_token_and_locale(&token, false);
+    let client = build_graphql_client(&token, global.transcript.as_ref())?;
+    let options = FetchOptions {
+        include_resolved: true,
+        include_outdated: true,
+    };
+    let threads = fetch_review_threads_with_options(&client, &repo, number, options).await?;
+    let comment = thread_for_comment(threads, comment_id)
+        .and_then(|thread| thread.comments.nodes.into_iter().next())
+        .ok_or(VkError::CommentNotFound { comment_id })?;
+
+    let mut blocks = suggestions(&comment.body).into_iter();
+    let replacement = blocks.next().ok_or(VkError::NoSuggestion { comment_id })?;
+    if blocks.next().is_some() {
+        warn!("comment {comment_id} has several suggestions; applying the first");
+    }
+    let conflict = |reason: String| VkError::SuggestionConflict {
+        path: comment.path.as_str().into(),
+        reason: reason.into_boxed_str(),
+    };
+    let commented = CommentedLines::of(&comment)
+        .ok_or_else(|| conflict("the diff hunk does not show the commented lines".into()))?;
+    let file = worktree_root()
+        .ok_or(VkError::RepoNotFound)?
+        .join(&comment.path);
+    let text = std::fs::read_to_string(&file)?;
+    let updated =
+        apply_suggestion(&text, &commented, &replacement).map_err(|e| conflict(e.to_string()))?;
+
+    if args.dry_run {
+        let patch = DiffOptions::new()
+            .set_original_filename(format!("a/{}", comment.path))
+            .set_modified_filename(format!("b/{}", comment.path))
+            .create_patch(&text, &updated);
+        let mut out = std::io::stdout().lock();
+        return finish_document(write!(out, "{patch}").map_err(anyhow::Error::from));
+    }
+    std::fs::write(&file, updated)?;
+    let mut out = std::io::stdout().lock();
+    finish_document(
+        writeln!(
+            out,
+            "Applied suggestion to {}:{}",
+            comment.path, commented.start
+        )
+        .map_err(anyhow::Error::from),
+    )
+}
+
+/// Choose the glyphs for human-readable output: plain ASCII when `--ascii`
+/// is given or the locale does not use UTF-8, emoji otherwise.
+fn output_icons(global: &GlobalArgs) -> Icons {
+    Icons::select(global.ascii || !locale_is_utf8())
+}
+
 fn locale_is_utf8() -> bool {
     for key in ["LC_ALL", "LC_CTYPE", "LANG"] {
         if let Ok(value) = environment::var(key) {
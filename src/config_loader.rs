@@ -136,10 +136,11 @@ mod tests {
     use super::{
         EXPLICIT_CONFIG_PATH_ENV, load_global_args_without_cli_overrides_from_process_args,
     };
-    use crate::cli_args::GlobalArgs;
+    use crate::cli_args::{Colour, GlobalArgs, ThemeName};
     use crate::test_utils::EnvSandbox;
     use serial_test::serial;
     use std::ffi::OsString;
+    use termimad::crossterm::style::Color;
     use vk::environment;
 
     fn setup_global_args_without_cli_overrides<I, F>(configure: F) -> (EnvSandbox, GlobalArgs)
@@ -184,6 +185,39 @@ mod tests {
         assert_eq!(global.repo.as_deref(), Some("from-config-path"));
     }
 
+    #[test]
+    #[serial]
+    fn load_global_args_without_cli_overrides_reads_theme_colours() {
+        let (_sandbox, global) = setup_global_args_without_cli_overrides(|sandbox| {
+            let config_path = sandbox.path().join("theme.toml");
+            std::fs::write(
+                &config_path,
+                "theme = \"light\"\n\n[colours]\nheader = \"dark_blue\"\nadded = \"#c8f0c8\"\nauthor = 208\n",
+            )
+            .expect("write config");
+
+            [
+                OsString::from("vk"),
+                OsString::from("--config-path"),
+                config_path.into_os_string(),
+            ]
+        });
+
+        assert_eq!(global.theme, Some(ThemeName::Light));
+        let colours = global.colours.expect("colours table");
+        assert_eq!(colours.header, Some(Colour(Color::DarkBlue)));
+        assert_eq!(
+            colours.added,
+            Some(Colour(Color::Rgb {
+                r: 0xc8,
+                g: 0xf0,
+                b: 0xc8
+            }))
+        );
+        assert_eq!(colours.author, Some(Colour(Color::AnsiValue(208))));
+        assert_eq!(colours.removed, None);
+    }
+
     #[test]
     #[serial]
     fn load_global_args_without_cli_overrides_reports_broken_explicit_config() {
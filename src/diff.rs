@@ -5,6 +5,7 @@ use std::fmt::Write;
 use std::sync::LazyLock;
 
 use crate::ReviewComment;
+use crate::cli_args::DiffContext;
 
 /// Width of the line number gutter in diff output
 pub const GUTTER_WIDTH: usize = 5;
@@ -50,7 +51,9 @@ where
     parsed
 }
 
-fn num_disp(num: i32) -> String {
+/// Right-align a line number in the gutter, keeping its last digits when it
+/// is too wide.
+pub fn num_disp(num: i32) -> String {
     let mut s = num.to_string();
     if s.len() > GUTTER_WIDTH {
         let start = s.len() - GUTTER_WIDTH;
@@ -59,28 +62,12 @@ fn num_disp(num: i32) -> String {
     format!("{s:>GUTTER_WIDTH$}")
 }
 
-/// Format a diff hunk, annotating line numbers and truncating output.
-///
-/// The returned string is limited to at most 20 lines centred on the
-/// comment's target line where possible.
-///
-/// # Examples
-/// ```ignore
-/// use vk::diff::format_comment_diff;
-/// # use vk::ReviewComment;
-/// let comment = ReviewComment {
-///     body: String::new(),
-///     diff_hunk: "@@ -1 +1 @@\n-line\n+line".into(),
-///     original_position: Some(1),
-///     position: Some(1),
-///     path: String::new(),
-///     url: String::new(),
-///     author: None,
-/// };
-/// let diff = format_comment_diff(&comment).unwrap();
-/// assert!(diff.contains("-line"));
-/// ```
-pub fn format_comment_diff(comment: &ReviewComment) -> Result<String, std::fmt::Error> {
+/// A parsed hunk line: old line number, new line number, and text with its
+/// `+`, `-`, or space prefix.
+type DiffLine = (Option<i32>, Option<i32>, String);
+
+/// Parse a comment's diff hunk, numbering lines from its header when valid.
+fn parse_comment_hunk(comment: &ReviewComment) -> Vec<DiffLine> {
     let diff_lines: Vec<&str> = comment
         .diff_hunk
         .lines()
@@ -88,10 +75,10 @@ pub fn format_comment_diff(comment: &ReviewComment) -> Result<String, std::fmt::
         .collect();
     let mut lines_iter = diff_lines.iter().copied();
     let Some(header) = lines_iter.next() else {
-        return Ok(String::new());
+        return Vec::new();
     };
 
-    let lines: Vec<(Option<i32>, Option<i32>, String)> = HUNK_RE.captures(header).map_or_else(
+    HUNK_RE.captures(header).map_or_else(
         || parse_diff_lines(diff_lines.iter().copied(), None, None),
         |caps| {
             let old_start: i32 = caps
@@ -113,36 +100,297 @@ pub fn format_comment_diff(comment: &ReviewComment) -> Result<String, std::fmt::
 
             parse_diff_lines(lines_iter, Some(old_start), Some(new_start))
         },
-    );
+    )
+}
 
-    let target_idx = lines
-        .iter()
-        .position(|(o, n, _)| comment.original_position == *o || comment.position == *n);
-    let (start, end) = target_idx.map_or_else(
-        || (0, std::cmp::min(lines.len(), 20)),
-        |idx| (idx.saturating_sub(5), std::cmp::min(lines.len(), idx + 6)),
-    );
+/// Locate the hunk line a comment's position fields point at.
+///
+/// `position` is matched against new-file line numbers first, then
+/// `original_position` against old-file numbers. Absent fields never match,
+/// so removed or added lines without a number on one side are not picked by
+/// accident.
+fn target_index(lines: &[DiffLine], comment: &ReviewComment) -> Option<usize> {
+    let find = |wanted: Option<i32>, side: fn(&DiffLine) -> Option<i32>| {
+        wanted.and_then(|w| lines.iter().position(|line| side(line) == Some(w)))
+    };
+    find(comment.position, |(_, n, _)| *n)
+        .or_else(|| find(comment.original_position, |(o, _, _)| *o))
+}
+
+/// Minimum number of lines shown when no target line can be located.
+const UNTARGETED_LINES: usize = 20;
+
+/// Lines selected for display together with the counts hidden either side.
+struct Window<'a> {
+    lines: &'a [DiffLine],
+    elided_before: usize,
+    elided_after: usize,
+}
 
+/// Select the lines shown for a comment.
+///
+/// With [`DiffContext::Lines`], the window spans that many lines either
+/// side of the target line. Without a target, the first lines of the hunk
+/// are shown: at least 20, or the full window size when that is larger.
+fn comment_window<'a>(
+    lines: &'a [DiffLine],
+    comment: &ReviewComment,
+    context: DiffContext,
+) -> Window<'a> {
+    let (start, end) = match context {
+        DiffContext::Full => (0, lines.len()),
+        DiffContext::Lines(n) => target_index(lines, comment).map_or_else(
+            || {
+                let span = std::cmp::max(UNTARGETED_LINES, n.saturating_mul(2).saturating_add(1));
+                (0, std::cmp::min(lines.len(), span))
+            },
+            |idx| {
+                let end = idx.saturating_add(n).saturating_add(1);
+                (idx.saturating_sub(n), std::cmp::min(lines.len(), end))
+            },
+        ),
+    };
+    Window {
+        lines: lines.get(start..end).unwrap_or(&[]),
+        elided_before: start,
+        elided_after: lines.len().saturating_sub(end),
+    }
+}
+
+/// Describe `count` hidden lines, e.g. `3 lines elided`.
+fn elision_note(count: usize) -> String {
+    let noun = if count == 1 { "line" } else { "lines" };
+    format!("{count} {noun} elided")
+}
+
+/// Write a gutter-aligned marker for hidden lines when `count` is non-zero.
+fn write_elision_marker(out: &mut String, count: usize) -> std::fmt::Result {
+    if count == 0 {
+        return Ok(());
+    }
+    writeln!(out, "{:>GUTTER_WIDTH$} {}", "...", elision_note(count))
+}
+
+/// Format a diff hunk, annotating line numbers and truncating output.
+///
+/// `context` selects how many lines surround the comment's target line.
+/// Hidden lines are reported by a `... N lines elided` marker above or below
+/// the shown lines.
+///
+/// # Examples
+/// ```ignore
+/// use vk::diff::format_comment_diff;
+/// # use vk::{ReviewComment, cli_args::DiffContext};
+/// let comment = ReviewComment {
+///     body: String::new(),
+///     diff_hunk: "@@ -1 +1 @@\n-line\n+line".into(),
+///     original_position: Some(1),
+///     position: Some(1),
+///     start_line: None,
+///     path: String::new(),
+///     url: String::new(),
+///     author: None,
+///     head_context: None,
+///     local_line: None,
+/// };
+/// let diff = format_comment_diff(&comment, DiffContext::default()).unwrap();
+/// assert!(diff.contains("-line"));
+/// ```
+pub fn format_comment_diff(
+    comment: &ReviewComment,
+    context: DiffContext,
+) -> Result<String, std::fmt::Error> {
+    let lines = parse_comment_hunk(comment);
+    let window = comment_window(&lines, comment, context);
     let mut out = String::new();
-    for (o, n, text) in lines.get(start..end).unwrap_or(&[]) {
+    write_elision_marker(&mut out, window.elided_before)?;
+    for (o, n, text) in window.lines {
         // Prefer the new line number, fall back to old, or blanks if neither
         let disp = n.or(*o).map_or_else(|| " ".repeat(GUTTER_WIDTH), num_disp);
 
         writeln!(&mut out, "{disp}|{text}")?;
     }
+    write_elision_marker(&mut out, window.elided_after)?;
+    Ok(out)
+}
+
+/// Determine the file line a comment refers to.
+///
+/// The line is taken from the hunk line matched by the comment's
+/// `position` or `original_position`, preferring the new-file number. When
+/// neither matches, the last numbered line of the hunk is used because
+/// GitHub ends a comment's hunk at the commented line. Returns `None` when
+/// the hunk header is missing or invalid.
+///
+/// # Examples
+/// ```ignore
+/// use vk::diff::comment_line;
+/// # use vk::ReviewComment;
+/// let comment = ReviewComment {
+///     diff_hunk: "@@ -1,2 +1,2 @@\n same\n-old\n+new".into(),
+///     ..Default::default()
+/// };
+/// assert_eq!(comment_line(&comment), Some(2));
+/// ```
+#[must_use]
+pub fn comment_line(comment: &ReviewComment) -> Option<u32> {
+    let lines = parse_comment_hunk(comment);
+    let line_number = |(o, n, _): &DiffLine| n.or(*o);
+    let line = target_index(&lines, comment)
+        .and_then(|idx| lines.get(idx))
+        .and_then(line_number)
+        .or_else(|| lines.iter().rev().find_map(line_number))?;
+    u32::try_from(line).ok().filter(|l| *l > 0)
+}
+
+/// Return up to `span` lines of the commented code, ending at the commented
+/// line, as they read in the commit the comment was made on.
+///
+/// Removed lines are skipped and the `+` or space prefix is stripped, so the
+/// result can be compared with the file on disk. Without a target line the
+/// region ends at the last line of the hunk.
+///
+/// # Examples
+/// ```ignore
+/// use vk::diff::commented_region;
+/// # use vk::ReviewComment;
+/// let comment = ReviewComment {
+///     diff_hunk: "@@ -1,2 +1,2 @@\n same\n-old\n+new".into(),
+///     ..Default::default()
+/// };
+/// assert_eq!(commented_region(&comment, 3), ["same", "new"]);
+/// ```
+#[must_use]
+pub fn commented_region(comment: &ReviewComment, span: usize) -> Vec<String> {
+    let lines = parse_comment_hunk(comment);
+    let end = target_index(&lines, comment).map_or(lines.len(), |idx| idx + 1);
+    let mut region: Vec<String> = lines
+        .get(..end)
+        .unwrap_or_default()
+        .iter()
+        .rev()
+        .filter(|(_, _, text)| !text.starts_with('-'))
+        .take(span)
+        .map(|(_, _, text)| text.get(1..).unwrap_or_default().to_owned())
+        .collect();
+    region.reverse();
+    region
+}
+
+/// Return the new-side lines `start..=end` of a comment's hunk without
+/// their diff prefix.
+///
+/// Returns `None` unless the hunk has a valid header and covers the whole
+/// range.
+#[must_use]
+pub fn hunk_lines(comment: &ReviewComment, start: u32, end: u32) -> Option<Vec<String>> {
+    let wanted = start..=end;
+    let lines: Vec<String> = parse_comment_hunk(comment)
+        .into_iter()
+        .filter(|(_, n, _)| {
+            n.and_then(|n| u32::try_from(n).ok())
+                .is_some_and(|n| wanted.contains(&n))
+        })
+        .map(|(_, _, text)| text.get(1..).unwrap_or_default().to_owned())
+        .collect();
+    let expected = usize::try_from(end.checked_sub(start)?).ok()? + 1;
+    (lines.len() == expected).then_some(lines)
+}
+
+/// Extract the lines shown by [`format_comment_diff`] as a unified diff.
+///
+/// The excerpt starts with a hunk header renumbered for the selected window
+/// when the original header was valid, so the result can be placed in a
+/// fenced `diff` block without a line-number gutter. Elided lines are noted
+/// after the header, where unified diffs allow free text.
+///
+/// # Examples
+/// ```ignore
+/// use vk::diff::comment_diff_excerpt;
+/// # use vk::{ReviewComment, cli_args::DiffContext};
+/// let comment = ReviewComment {
+///     diff_hunk: "@@ -1 +1 @@\n-old\n+new".into(),
+///     ..Default::default()
+/// };
+/// let excerpt = comment_diff_excerpt(&comment, DiffContext::default()).unwrap();
+/// assert_eq!(excerpt, "@@ -1,1 +1,1 @@\n-old\n+new\n");
+/// ```
+pub fn comment_diff_excerpt(
+    comment: &ReviewComment,
+    context: DiffContext,
+) -> Result<String, std::fmt::Error> {
+    let lines = parse_comment_hunk(comment);
+    let window = comment_window(&lines, comment, context);
+    let mut out = String::new();
+    let old_start = window.lines.iter().find_map(|(o, _, _)| *o);
+    let new_start = window.lines.iter().find_map(|(_, n, _)| *n);
+    if let (Some(old), Some(new)) = (old_start, new_start) {
+        let old_count = window.lines.iter().filter(|(o, _, _)| o.is_some()).count();
+        let new_count = window.lines.iter().filter(|(_, n, _)| n.is_some()).count();
+        write!(&mut out, "@@ -{old},{old_count} +{new},{new_count} @@")?;
+        let notes: Vec<String> = [
+            (window.elided_before, "above"),
+            (window.elided_after, "below"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, side)| format!("{} {side}", elision_note(count)))
+        .collect();
+        if !notes.is_empty() {
+            write!(&mut out, " {}", notes.join(", "))?;
+        }
+        writeln!(&mut out)?;
+    }
+    for (_, _, text) in window.lines {
+        writeln!(&mut out, "{text}")?;
+    }
     Ok(out)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::rstest;
     use std::fmt::Write;
 
+    #[rstest]
+    #[case(2, 3, Some(vec!["two".to_owned(), "three".to_owned()]))]
+    #[case(4, 5, None)]
+    #[case(3, 2, None)]
+    fn hunk_lines_cover_range(
+        #[case] start: u32,
+        #[case] end: u32,
+        #[case] expected: Option<Vec<String>>,
+    ) {
+        let comment = ReviewComment {
+            diff_hunk: "@@ -1,4 +1,4 @@\n one\n-gone\n+two\n three\n four".into(),
+            ..Default::default()
+        };
+        assert_eq!(hunk_lines(&comment, start, end), expected);
+    }
+
+    #[rstest]
+    #[case::ends_at_target(Some(3), 2, &["two", "three"])]
+    #[case::skips_removed_lines(Some(3), 5, &["one", "two", "three"])]
+    #[case::defaults_to_hunk_end(None, 2, &["three", "four"])]
+    fn commented_region_cases(
+        #[case] position: Option<i32>,
+        #[case] span: usize,
+        #[case] expected: &[&str],
+    ) {
+        let comment = ReviewComment {
+            diff_hunk: "@@ -1,4 +1,4 @@\n one\n-gone\n+two\n three\n four".into(),
+            position,
+            ..Default::default()
+        };
+        assert_eq!(commented_region(&comment, span), expected);
+    }
+
     #[test]
     fn format_comment_diff_sample() {
         let data = include_str!("../tests/fixtures/review_comment.json");
         let comment: ReviewComment = serde_json::from_str(data).expect("deserialize");
-        let diff = format_comment_diff(&comment).expect("diff");
+        let diff = format_comment_diff(&comment, DiffContext::default()).expect("diff");
         assert!(diff.contains("-import dataclasses"));
         assert!(diff.contains("import typing"));
     }
@@ -175,11 +423,19 @@ mod tests {
             diff_hunk: "not a hunk\n-line1\n+line1".to_string(),
             original_position: None,
             position: None,
+            start_line: None,
             path: String::new(),
             url: String::new(),
             author: None,
+            database_id: None,
+            created_at: None,
+            updated_at: None,
+            last_edited_at: None,
+            reactions: Vec::new(),
+            head_context: None,
+            local_line: None,
         };
-        let out = format_comment_diff(&comment).expect("diff");
+        let out = format_comment_diff(&comment, DiffContext::default()).expect("diff");
         assert!(out.contains("-line1"));
         assert!(out.contains("+line1"));
     }
@@ -195,11 +451,124 @@ mod tests {
             diff_hunk: diff,
             original_position: None,
             position: None,
+            start_line: None,
             path: String::new(),
             url: String::new(),
             author: None,
+            database_id: None,
+            created_at: None,
+            updated_at: None,
+            last_edited_at: None,
+            reactions: Vec::new(),
+            head_context: None,
+            local_line: None,
+        };
+        let out = format_comment_diff(&comment, DiffContext::default()).expect("diff");
+        assert_eq!(out.lines().count(), 21);
+        assert_eq!(out.lines().last(), Some("  ... 10 lines elided"));
+    }
+
+    fn long_hunk_comment(position: i32) -> ReviewComment {
+        let mut diff = String::from("@@ -1,30 +1,30 @@\n");
+        for i in 1..=30 {
+            writeln!(&mut diff, " line{i}").expect("write diff line");
+        }
+        ReviewComment {
+            diff_hunk: diff,
+            position: Some(position),
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case(DiffContext::Lines(2), vec!["  ... 13 lines elided", "   14| line14", "   15| line15", "   16| line16", "   17| line17", "   18| line18", "  ... 12 lines elided"])]
+    #[case(DiffContext::Lines(0), vec!["  ... 15 lines elided", "   16| line16", "  ... 14 lines elided"])]
+    fn format_comment_diff_honours_context(
+        #[case] context: DiffContext,
+        #[case] expected: Vec<&str>,
+    ) {
+        let out = format_comment_diff(&long_hunk_comment(16), context).expect("diff");
+        assert_eq!(out.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn format_comment_diff_full_context_shows_whole_hunk() {
+        let out = format_comment_diff(&long_hunk_comment(16), DiffContext::Full).expect("diff");
+        assert_eq!(out.lines().count(), 30);
+        assert!(!out.contains("elided"));
+    }
+
+    #[rstest]
+    #[case("7", DiffContext::Lines(7))]
+    #[case("full", DiffContext::Full)]
+    #[case("FULL", DiffContext::Full)]
+    fn diff_context_parses(#[case] input: &str, #[case] expected: DiffContext) {
+        assert_eq!(input.parse::<DiffContext>(), Ok(expected));
+    }
+
+    #[test]
+    fn diff_context_rejects_garbage() {
+        assert!("lots".parse::<DiffContext>().is_err());
+    }
+
+    #[rstest]
+    #[case(Some(2), None, Some(2))]
+    #[case(None, Some(3), Some(3))]
+    #[case(None, None, Some(3))]
+    fn comment_line_prefers_matched_position(
+        #[case] position: Option<i32>,
+        #[case] original_position: Option<i32>,
+        #[case] expected: Option<u32>,
+    ) {
+        let comment = ReviewComment {
+            diff_hunk: "@@ -1,3 +1,3 @@\n one\n-two\n+two!\n three".into(),
+            position,
+            original_position,
+            ..Default::default()
+        };
+        assert_eq!(comment_line(&comment), expected);
+    }
+
+    #[test]
+    fn comment_line_requires_hunk_header() {
+        let comment = ReviewComment {
+            diff_hunk: "not a hunk\n+line".into(),
+            position: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(comment_line(&comment), None);
+    }
+
+    #[test]
+    fn comment_diff_excerpt_renumbers_window() {
+        let mut diff = String::from("@@ -10,30 +20,30 @@\n");
+        for i in 0..30 {
+            writeln!(&mut diff, " line{i}").expect("write diff line");
+        }
+        let comment = ReviewComment {
+            diff_hunk: diff,
+            position: Some(35),
+            ..Default::default()
+        };
+        let out = comment_diff_excerpt(&comment, DiffContext::default()).expect("excerpt");
+        let mut lines = out.lines();
+        // Line 35 in the new file is the sixteenth context line, so the
+        // window opens five lines earlier.
+        assert_eq!(
+            lines.next(),
+            Some("@@ -20,11 +30,11 @@ 10 lines elided above, 9 lines elided below")
+        );
+        assert_eq!(lines.next(), Some(" line10"));
+        assert_eq!(out.lines().count(), 12);
+    }
+
+    #[test]
+    fn comment_diff_excerpt_omits_header_without_line_numbers() {
+        let comment = ReviewComment {
+            diff_hunk: "not a hunk\n-line1\n+line1".to_string(),
+            ..Default::default()
         };
-        let out = format_comment_diff(&comment).expect("diff");
-        assert_eq!(out.lines().count(), 20);
+        let out = comment_diff_excerpt(&comment, DiffContext::default()).expect("excerpt");
+        assert_eq!(out, " not a hunk\n-line1\n+line1\n");
     }
 }
@@ -0,0 +1,210 @@
+//! Filtering review threads by the path of their first comment.
+//!
+//! File arguments to `vk pr` are gitignore-style patterns. A plain path
+//! selects that file or, when it names a directory, everything below it.
+//! Patterns without a slash, such as `*.rs`, match at any depth, while `*`
+//! never crosses a `/` in patterns that contain one. A leading `!` excludes
+//! matching paths, and exclusions win over inclusions; with only exclusions,
+//! every other path is kept. Every pattern also matches the path it spells
+//! literally, so file names containing `[` or `{` still select themselves.
+//!
+//! GitHub reports paths relative to the repository root, so patterns are
+//! resolved against the directory `vk` runs in: `.` inside `src/api` selects
+//! `src/api/**`, and `*.rs` there matches Rust files below `src/api` only.
+
+use crate::VkError;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+
+/// Compiled file patterns a thread's path must match to be shown.
+#[derive(Debug, Clone, Default)]
+pub struct FileFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl FileFilter {
+    /// Compile `patterns` given relative to `prefix`.
+    ///
+    /// `prefix` is the current directory relative to the repository root,
+    /// as printed by `git rev-parse --show-prefix`, or empty at the top level
+    /// and outside a checkout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VkError::InvalidFilePattern`] when a pattern is not a valid
+    /// glob or climbs above the repository root.
+    pub fn new(patterns: &[String], prefix: &str) -> Result<Self, VkError> {
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+        for pattern in patterns {
+            let (negated, body) = pattern
+                .strip_prefix('!')
+                .map_or((false, pattern.as_str()), |rest| (true, rest));
+            let globs = resolve(body, prefix).map_err(|reason| invalid(pattern, reason))?;
+            let target = if negated { &mut exclude } else { &mut include };
+            for glob in globs {
+                target.push(compile(&glob).map_err(|e| invalid(pattern, &e.kind().to_string()))?);
+            }
+        }
+        Ok(Self {
+            include: build_set(include, patterns)?,
+            exclude: build_set(exclude, patterns)?,
+        })
+    }
+
+    /// Whether any pattern was given.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.include.is_some() || self.exclude.is_some()
+    }
+
+    /// Whether the repository-relative `path` passes the filter.
+    #[must_use]
+    pub fn matches(&self, path: &str) -> bool {
+        self.include.as_ref().is_none_or(|set| set.is_match(path))
+            && !self.exclude.as_ref().is_some_and(|set| set.is_match(path))
+    }
+}
+
+/// Translate one pattern into root-relative globs.
+///
+/// A path also matches everything below it, so each glob is paired with a
+/// `/**` variant; a trailing slash keeps only that variant. Patterns with
+/// glob metacharacters also match the path they spell literally, so
+/// `app/[id]/page.tsx` still selects that file.
+fn resolve(pattern: &str, prefix: &str) -> Result<Vec<String>, &'static str> {
+    let dir_only = pattern.ends_with('/');
+    let body = pattern.trim_end_matches('/');
+    if body.is_empty() {
+        return Err("empty pattern");
+    }
+    let floating = !body.contains('/') && body != "." && body != "..";
+    let base = if floating {
+        let dir = normalise(prefix, "")?;
+        if dir.is_empty() {
+            format!("**/{body}")
+        } else {
+            format!("{dir}/**/{body}")
+        }
+    } else {
+        normalise(prefix, body.trim_start_matches('/'))?
+    };
+    if base.is_empty() {
+        return Ok(vec!["**".into()]);
+    }
+    let literal = normalise(prefix, &globset::escape(body.trim_start_matches('/')))?;
+    let mut paths = vec![base];
+    if !paths.contains(&literal) {
+        paths.push(literal);
+    }
+    let mut globs = Vec::new();
+    for path in paths {
+        if !dir_only {
+            globs.push(path.clone());
+        }
+        globs.push(format!("{path}/**"));
+    }
+    Ok(globs)
+}
+
+/// Join `path` onto `prefix`, resolving `.` and `..` segments.
+fn normalise(prefix: &str, path: &str) -> Result<String, &'static str> {
+    let mut parts: Vec<String> = prefix
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(globset::escape)
+        .collect();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop().ok_or("pattern leaves the repository")?;
+            }
+            other => parts.push(other.to_owned()),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+fn compile(glob: &str) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(glob).literal_separator(true).build()
+}
+
+fn build_set(globs: Vec<Glob>, patterns: &[String]) -> Result<Option<GlobSet>, VkError> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| invalid(&patterns.join(" "), &e.to_string()))
+}
+
+fn invalid(pattern: &str, reason: &str) -> VkError {
+    VkError::InvalidFilePattern {
+        pattern: pattern.into(),
+        reason: reason.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn filter(patterns: &[&str], prefix: &str) -> FileFilter {
+        let patterns: Vec<String> = patterns.iter().map(ToString::to_string).collect();
+        FileFilter::new(&patterns, prefix).expect("valid patterns")
+    }
+
+    #[rstest]
+    #[case(&["src/lib.rs"], "", "src/lib.rs", true)]
+    #[case(&["src/lib.rs"], "", "src/lib.rs.orig", false)]
+    #[case(&["src"], "", "src/api/mod.rs", true)]
+    #[case(&["src/"], "", "src/api/mod.rs", true)]
+    #[case(&["src/"], "", "srcs/main.rs", false)]
+    #[case(&["*.rs"], "", "src/api/mod.rs", true)]
+    #[case(&["src/*.rs"], "", "src/api/mod.rs", false)]
+    #[case(&["src/**/*.rs"], "", "src/api/mod.rs", true)]
+    #[case(&["!tests/**"], "", "tests/cli.rs", false)]
+    #[case(&["!tests/**"], "", "src/lib.rs", true)]
+    #[case(&["src", "!src/gen/"], "", "src/gen/schema.rs", false)]
+    #[case(&["."], "src/api/", "src/api/mod.rs", true)]
+    #[case(&["."], "src/api/", "src/lib.rs", false)]
+    #[case(&["*.rs"], "src/api/", "src/lib.rs", false)]
+    #[case(&["../lib.rs"], "src/api/", "src/lib.rs", true)]
+    #[case(&["/docs"], "", "docs/users-guide.md", true)]
+    #[case(&["."], "", "README.md", true)]
+    #[case(&["app/[id]/page.tsx"], "", "app/[id]/page.tsx", true)]
+    #[case(&["app/[id]/page.tsx"], "", "app/i/page.tsx", true)]
+    #[case(&["[id]"], "app/", "app/[id]/page.tsx", true)]
+    #[case(&["src/{legacy}.rs"], "", "src/{legacy}.rs", true)]
+    #[case(&["!app/[id]/"], "", "app/[id]/page.tsx", false)]
+    fn paths_are_matched(
+        #[case] patterns: &[&str],
+        #[case] prefix: &str,
+        #[case] path: &str,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(filter(patterns, prefix).matches(path), expected);
+    }
+
+    #[rstest]
+    #[case("..")]
+    #[case("src/[")]
+    fn bad_patterns_are_rejected(#[case] pattern: &str) {
+        let err = FileFilter::new(&[pattern.to_owned()], "").expect_err("invalid pattern");
+        assert!(matches!(err, VkError::InvalidFilePattern { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn no_patterns_keep_everything() {
+        let filter = filter(&[], "src/");
+        assert!(!filter.is_active());
+        assert!(filter.matches("README.md"));
+    }
+}
@@ -9,15 +9,24 @@ pub const THREADS_QUERY: &str = r"
               id
               isResolved
                 isOutdated
+              resolvedBy { login }
+              viewerCanResolve
+              viewerCanReply
               comments(first: 100) {
                 nodes {
                   body
                   diffHunk
                   originalPosition
                   position
+                  startLine
                   path
                   url
                   author { login }
+                  databaseId
+                  createdAt
+                  updatedAt
+                  lastEditedAt
+                  reactionGroups { content reactors { totalCount } }
                 }
                 pageInfo { hasNextPage endCursor }
               }
@@ -39,9 +48,15 @@ pub const COMMENT_QUERY: &str = r"
               diffHunk
               originalPosition
               position
+              startLine
               path
               url
               author { login }
+              databaseId
+              createdAt
+              updatedAt
+              lastEditedAt
+              reactionGroups { content reactors { totalCount } }
             }
             pageInfo { hasNextPage endCursor }
           }
@@ -80,3 +95,47 @@ pub const PR_FOR_BRANCH_QUERY: &str = r"
       }
     }
 ";
+
+/// Query for the commit at the head of a pull request.
+pub const PR_HEAD_QUERY: &str = r"
+    query($owner: String!, $name: String!, $number: Int!) {
+      repository(owner: $owner, name: $name) {
+        pullRequest(number: $number) {
+          headRefOid
+        }
+      }
+    }
+";
+
+/// Query for the contents of a file at a commit.
+///
+/// `$expression` takes the `<oid>:<path>` form accepted by
+/// `Repository.object`. `text` is null for binary or very large blobs.
+pub const BLOB_QUERY: &str = r"
+    query($owner: String!, $name: String!, $expression: String!) {
+      repository(owner: $owner, name: $name) {
+        object(expression: $expression) {
+          ... on Blob {
+            isBinary
+            text
+          }
+        }
+      }
+    }
+";
+
+/// Query for the date of a commit.
+///
+/// `$expression` is a full or abbreviated commit SHA. `committedDate` is null
+/// when the expression names an object other than a commit.
+pub const COMMIT_DATE_QUERY: &str = r"
+    query($owner: String!, $name: String!, $expression: String!) {
+      repository(owner: $owner, name: $name) {
+        object(expression: $expression) {
+          ... on Commit {
+            committedDate
+          }
+        }
+      }
+    }
+";
@@ -0,0 +1,322 @@
+//! Review context read from files at the pull request head commit.
+//!
+//! GitHub truncates a comment's diff hunk at the commented line, which often
+//! hides the code a reviewer is talking about. With `--expand-context`, the
+//! commented file is fetched at the head commit and the lines around the
+//! comment replace the hunk. Blobs are cached per path so several comments on
+//! one file cost a single request.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, json};
+
+use crate::cli_args::DiffContext;
+use crate::diff::{GUTTER_WIDTH, comment_line, num_disp};
+use crate::graphql_queries::{BLOB_QUERY, PR_HEAD_QUERY};
+use crate::ref_parser::RepoInfo;
+use crate::{GraphQLClient, ReviewThread, VkError};
+
+/// Lines of a file surrounding a commented line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileExcerpt {
+    /// Line number of the first entry in `lines`.
+    pub start_line: u32,
+    /// Line number the comment refers to.
+    pub target_line: u32,
+    /// File lines without trailing newlines.
+    pub lines: Vec<String>,
+}
+
+impl FileExcerpt {
+    /// Cut `context` lines either side of `target_line` from `text`.
+    ///
+    /// Returns `None` when the file has fewer than `target_line` lines, for
+    /// example because the comment predates later edits.
+    #[must_use]
+    pub fn around(text: &str, target_line: u32, context: DiffContext) -> Option<Self> {
+        let all: Vec<&str> = text.lines().collect();
+        let target_idx = usize::try_from(target_line).ok()?.checked_sub(1)?;
+        if target_idx >= all.len() {
+            return None;
+        }
+        let (start, end) = match context {
+            DiffContext::Full => (0, all.len()),
+            DiffContext::Lines(n) => (
+                target_idx.saturating_sub(n),
+                std::cmp::min(all.len(), target_idx.saturating_add(n).saturating_add(1)),
+            ),
+        };
+        Some(Self {
+            start_line: u32::try_from(start).ok()?.checked_add(1)?,
+            target_line,
+            lines: all
+                .get(start..end)?
+                .iter()
+                .map(|l| l.trim_end_matches('\r').to_owned())
+                .collect(),
+        })
+    }
+
+    /// Format the excerpt with a line-number gutter, marking the target line
+    /// with `>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the output string fails.
+    pub fn format(&self) -> Result<String, std::fmt::Error> {
+        let mut out = String::new();
+        for (line, text) in (self.start_line..).zip(&self.lines) {
+            let marker = if line == self.target_line { '>' } else { ' ' };
+            let disp = i32::try_from(line).map_or_else(|_| " ".repeat(GUTTER_WIDTH), num_disp);
+            writeln!(&mut out, "{disp}|{marker}{text}")?;
+        }
+        Ok(out)
+    }
+}
+
+#[derive(Deserialize)]
+struct HeadData {
+    repository: HeadRepository,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HeadRepository {
+    pull_request: HeadPullRequest,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HeadPullRequest {
+    head_ref_oid: String,
+}
+
+#[derive(Deserialize)]
+struct BlobData {
+    repository: BlobRepository,
+}
+
+#[derive(Deserialize)]
+struct BlobRepository {
+    object: Option<Blob>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Blob {
+    #[serde(default)]
+    is_binary: bool,
+    text: Option<String>,
+}
+
+/// Fetch the commit at the head of pull request `number`.
+///
+/// # Errors
+///
+/// Returns an error if the API request fails or the response is malformed.
+pub async fn fetch_head_oid(
+    client: &GraphQLClient,
+    repo: &RepoInfo,
+    number: u64,
+) -> Result<String, VkError> {
+    let mut vars = Map::new();
+    vars.insert("owner".into(), json!(repo.owner.clone()));
+    vars.insert("name".into(), json!(repo.name.clone()));
+    vars.insert("number".into(), json!(number));
+    let data: HeadData = client.fetch_page(PR_HEAD_QUERY, None, vars).await?;
+    Ok(data.repository.pull_request.head_ref_oid)
+}
+
+/// File contents at one commit, fetched on demand and cached by path.
+pub struct HeadFiles<'a> {
+    client: &'a GraphQLClient,
+    repo: &'a RepoInfo,
+    oid: String,
+    cache: HashMap<String, Option<String>>,
+}
+
+impl<'a> HeadFiles<'a> {
+    /// Read files from `repo` at commit `oid`.
+    #[must_use]
+    pub fn new(client: &'a GraphQLClient, repo: &'a RepoInfo, oid: String) -> Self {
+        Self {
+            client,
+            repo,
+            oid,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Return the text of `path`, or `None` when the file is missing or
+    /// binary at the commit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response is
+    /// malformed.
+    pub async fn text(&mut self, path: &str) -> Result<Option<&str>, VkError> {
+        if !self.cache.contains_key(path) {
+            let mut vars = Map::new();
+            vars.insert("owner".into(), json!(self.repo.owner.clone()));
+            vars.insert("name".into(), json!(self.repo.name.clone()));
+            vars.insert("expression".into(), json!(format!("{}:{path}", self.oid)));
+            let data: BlobData = self.client.fetch_page(BLOB_QUERY, None, vars).await?;
+            let text = data
+                .repository
+                .object
+                .filter(|blob| !blob.is_binary)
+                .and_then(|blob| blob.text);
+            self.cache.insert(path.to_owned(), text);
+        }
+        Ok(self.cache.get(path).and_then(Option::as_deref))
+    }
+}
+
+/// Attach head-commit excerpts to the first comment of each thread.
+///
+/// Only comments that still apply to the head commit (those with a
+/// `position`) are expanded. Comments whose file is missing, binary, or too
+/// short keep their diff hunk.
+///
+/// # Errors
+///
+/// Returns an error if any API request fails.
+pub async fn expand_threads(
+    files: &mut HeadFiles<'_>,
+    threads: &mut [ReviewThread],
+    context: DiffContext,
+) -> Result<(), VkError> {
+    for thread in threads {
+        let Some(first) = thread.comments.nodes.first_mut() else {
+            continue;
+        };
+        if first.position.is_none() {
+            continue;
+        }
+        let Some(line) = comment_line(first) else {
+            continue;
+        };
+        first.head_context = files
+            .text(&first.path)
+            .await?
+            .and_then(|text| FileExcerpt::around(text, line, context));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::start_server;
+    use crate::{CommentConnection, ReviewComment};
+    use rstest::rstest;
+    use std::sync::atomic::Ordering;
+
+    const FILE: &str = "one\ntwo\nthree\nfour\nfive\n";
+
+    #[rstest]
+    #[case(DiffContext::Lines(1), 2, &["two", "three", "four"])]
+    #[case(DiffContext::Lines(10), 1, &["one", "two", "three", "four", "five"])]
+    #[case(DiffContext::Full, 1, &["one", "two", "three", "four", "five"])]
+    fn excerpt_spans_context(
+        #[case] context: DiffContext,
+        #[case] start_line: u32,
+        #[case] expected: &[&str],
+    ) {
+        let excerpt = FileExcerpt::around(FILE, 3, context).expect("excerpt");
+        assert_eq!(excerpt.start_line, start_line);
+        assert_eq!(excerpt.lines, expected);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(6)]
+    fn excerpt_rejects_lines_outside_file(#[case] line: u32) {
+        assert_eq!(
+            FileExcerpt::around(FILE, line, DiffContext::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn excerpt_marks_target_line() {
+        let excerpt = FileExcerpt::around(FILE, 2, DiffContext::Lines(1)).expect("excerpt");
+        assert_eq!(
+            excerpt.format().expect("format"),
+            "    1| one\n    2|>two\n    3| three\n"
+        );
+    }
+
+    fn thread(path: &str) -> ReviewThread {
+        ReviewThread {
+            comments: CommentConnection {
+                nodes: vec![ReviewComment {
+                    diff_hunk: "@@ -1,2 +1,2 @@\n one\n+two".into(),
+                    position: Some(2),
+                    path: path.into(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn expand_threads_fetches_each_path_once() {
+        let head = json!({"data": {"repository": {"pullRequest": {"headRefOid": "abc"}}}});
+        let blob = json!({"data": {"repository": {"object": {"isBinary": false, "text": FILE}}}});
+        let server = start_server(vec![head.to_string(), blob.to_string()]);
+        let repo = RepoInfo {
+            owner: "o".into(),
+            name: "r".into(),
+        };
+        let oid = fetch_head_oid(&server.client, &repo, 1)
+            .await
+            .expect("head oid");
+        assert_eq!(oid, "abc");
+        let mut files = HeadFiles::new(&server.client, &repo, oid);
+        let mut threads = vec![thread("src/lib.rs"), thread("src/lib.rs")];
+        expand_threads(&mut files, &mut threads, DiffContext::Lines(1))
+            .await
+            .expect("expand");
+        assert_eq!(server.hits.load(Ordering::SeqCst), 2);
+        for t in &threads {
+            let excerpt = t
+                .comments
+                .nodes
+                .first()
+                .and_then(|c| c.head_context.as_ref())
+                .expect("excerpt");
+            assert_eq!(excerpt.lines, ["one", "two", "three"]);
+        }
+        server.join.abort();
+        let _ = server.join.await;
+    }
+
+    #[tokio::test]
+    async fn missing_files_keep_the_hunk() {
+        let blob = json!({"data": {"repository": {"object": null}}});
+        let server = start_server(vec![blob.to_string()]);
+        let repo = RepoInfo {
+            owner: "o".into(),
+            name: "r".into(),
+        };
+        let mut files = HeadFiles::new(&server.client, &repo, "abc".into());
+        let mut threads = vec![thread("gone.rs")];
+        expand_threads(&mut files, &mut threads, DiffContext::default())
+            .await
+            .expect("expand");
+        assert!(
+            threads
+                .first()
+                .and_then(|t| t.comments.nodes.first())
+                .is_some_and(|c| c.head_context.is_none())
+        );
+        server.join.abort();
+        let _ = server.join.await;
+    }
+}
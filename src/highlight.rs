@@ -0,0 +1,163 @@
+//! Language-aware highlighting of formatted diff hunks.
+//!
+//! Highlighting layers syntax colours chosen from the commented file's
+//! extension on top of a tinted background for removed and added lines. The
+//! colours and the `syntect` theme come from the active [`Palette`].
+//! Grammars and themes are bundled with `syntect`, so no network or system
+//! configuration is needed. Both are loaded on first use because they are
+//! only required when output goes to a terminal.
+
+use std::sync::LazyLock;
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::as_24_bit_terminal_escaped;
+
+use crate::theme::{Palette, bg_escape, fg_escape};
+
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Grammars for lines without their trailing newline, as diff lines are
+/// highlighted one at a time after splitting.
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_nonewlines);
+static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// Extensions without a bundled grammar mapped to the closest one that
+/// exists.
+fn extension_alias(ext: &str) -> &str {
+    match ext {
+        "ts" | "tsx" | "mts" | "cts" | "jsx" | "mjs" | "cjs" => "js",
+        other => other,
+    }
+}
+
+/// Pick a grammar for `path` from its extension or file name.
+fn syntax_for(path: &str) -> Option<&'static SyntaxReference> {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let ext = file_name.rsplit_once('.').map_or(file_name, |(_, ext)| ext);
+    SYNTAXES.find_syntax_by_extension(extension_alias(ext))
+}
+
+/// Highlight a diff produced by [`crate::diff::format_comment_diff`].
+///
+/// Each `gutter|<prefix><code>` line keeps its layout: the gutter is dimmed,
+/// the `+`/`-` prefix is coloured, and the code is highlighted for the
+/// language of `path` on a tinted background, all using `palette`. Lines are
+/// returned unchanged when no grammar matches or the palette has no syntax
+/// theme, except for the add/remove colouring.
+///
+/// # Examples
+///
+/// ```ignore
+/// use vk::highlight::highlight_diff;
+/// let out = highlight_diff("src/lib.rs", "   1|+fn main() {}\n", Palette::default());
+/// assert!(out.contains("\x1b["));
+/// ```
+#[must_use]
+pub fn highlight_diff(path: &str, diff: &str, palette: Palette) -> String {
+    let theme = palette
+        .syntax_theme
+        .and_then(|name| THEMES.themes.get(name));
+    let mut highlighter = syntax_for(path)
+        .zip(theme)
+        .map(|(syntax, theme)| HighlightLines::new(syntax, theme));
+    let added = (fg_escape(palette.added), bg_escape(palette.added_bg));
+    let removed = (fg_escape(palette.removed), bg_escape(palette.removed_bg));
+    let mut out = String::with_capacity(diff.len() * 2);
+    for line in diff.lines() {
+        let Some((gutter, text)) = line.split_once('|') else {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        };
+        let mut chars = text.chars();
+        let (marker, background) = match chars.next() {
+            Some('+') => (added.0.as_str(), added.1.as_str()),
+            Some('-') => (removed.0.as_str(), removed.1.as_str()),
+            _ => ("", ""),
+        };
+        let (prefix, code) = text.split_at(text.len() - chars.as_str().len());
+        out.push_str(DIM);
+        out.push_str(gutter);
+        out.push('|');
+        out.push_str(RESET);
+        out.push_str(background);
+        out.push_str(marker);
+        out.push_str(prefix);
+        let coloured = highlighter.as_mut().and_then(|h| {
+            h.highlight_line(code, &SYNTAXES)
+                .ok()
+                .map(|ranges| as_24_bit_terminal_escaped(&ranges, false))
+        });
+        out.push_str(coloured.as_deref().unwrap_or(code));
+        out.push_str(RESET);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::strip_ansi_codes;
+
+    const ADDED_BG: &str = "\x1b[48;2;16;56;24m";
+    const REMOVED_BG: &str = "\x1b[48;2;72;20;24m";
+    const ADDED_MARKER: &str = "\x1b[32m";
+
+    #[test]
+    fn highlighting_preserves_text() {
+        let diff = "   1| use std::io;\n   2|-fn old() {}\n   2|+fn new() {}\n";
+        let out = highlight_diff("src/lib.rs", diff, Palette::default());
+        assert_eq!(strip_ansi_codes(&out), diff);
+        assert!(out.contains(REMOVED_BG));
+        assert!(out.contains(ADDED_BG));
+        assert!(
+            out.contains("\x1b[38;2;"),
+            "expected syntax colours: {out:?}"
+        );
+    }
+
+    #[test]
+    fn unknown_languages_keep_diff_colours() {
+        let out = highlight_diff("notes.unknownext", "   1|+plain\n", Palette::default());
+        assert_eq!(
+            out,
+            format!("{DIM}   1|{RESET}{ADDED_BG}{ADDED_MARKER}+plain{RESET}\n")
+        );
+    }
+
+    #[test]
+    fn palettes_without_syntax_theme_leave_code_plain() {
+        let palette = Palette {
+            syntax_theme: None,
+            added_bg: None,
+            ..Palette::default()
+        };
+        let out = highlight_diff("src/lib.rs", "   1|+fn new() {}\n", palette);
+        assert_eq!(
+            out,
+            format!("{DIM}   1|{RESET}{ADDED_MARKER}+fn new() {{}}{RESET}\n")
+        );
+    }
+
+    #[test]
+    fn line_comments_end_with_their_line() {
+        let alone = highlight_diff("app.py", "   2|+y = 2\n", Palette::default());
+        let out = highlight_diff(
+            "app.py",
+            "   1|+x = 1 # note\n   2|+y = 2\n",
+            Palette::default(),
+        );
+        assert!(out.ends_with(&alone), "{out:?}");
+    }
+
+    #[test]
+    fn typescript_uses_javascript_grammar() {
+        assert!(syntax_for("web/app.tsx").is_some());
+        assert!(syntax_for("Makefile").is_some());
+        assert!(syntax_for("Cargo.toml").is_none());
+    }
+}
@@ -6,9 +6,88 @@ use html5ever::tendril::TendrilSink as _;
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
 use std::borrow::Cow;
 use std::default::Default;
+use std::fmt::Write as _;
+
+/// Default marker (U+25B6) prefixed to collapsed summaries.
+const COLLAPSED_MARKER: &str = "\u{25B6}";
 const CARRIAGE_RETURN: char = '\r';
 const LINE_FEED: char = '\n';
 
+/// Elements kept by [`sanitize_html`]; any other element is replaced by its
+/// content.
+const ALLOWED_ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "dd",
+    "del",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "ins",
+    "kbd",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "u",
+    "ul",
+];
+
+/// Elements dropped together with their content.
+const DROPPED_ELEMENTS: &[&str] = &[
+    "button", "embed", "form", "iframe", "noscript", "object", "script", "select", "style", "svg",
+    "template", "textarea", "title",
+];
+
+/// Allowed elements written without a closing tag.
+const VOID_ELEMENTS: &[&str] = &["br", "hr"];
+
+/// Attributes kept on each element; all others are dropped.
+const ALLOWED_ATTRIBUTES: &[(&str, &str)] = &[
+    ("a", "href"),
+    ("a", "title"),
+    ("abbr", "title"),
+    ("details", "open"),
+    ("ol", "start"),
+    ("td", "colspan"),
+    ("td", "rowspan"),
+    ("th", "colspan"),
+    ("th", "rowspan"),
+];
+
+/// URL schemes links may use.
+const SAFE_SCHEMES: &[&str] = &["https:", "http:", "mailto:"];
+
 /// Collapse root `<details>` blocks in the given text.
 ///
 /// Each root-level `<details>` tag is replaced by the contents of its
@@ -44,28 +123,172 @@ fn normalize_line_endings(input: &str) -> Cow<'_, str> {
 
 #[must_use]
 pub fn collapse_details(input: &str) -> String {
+    collapse_details_with(input, COLLAPSED_MARKER)
+}
+
+/// Collapse root `<details>` blocks like [`collapse_details`], prefixing
+/// each summary with `marker`.
+///
+/// # Examples
+///
+/// ```
+/// use vk::html::collapse_details_with;
+/// let input = "<details><summary>hi</summary><p>hidden</p></details>";
+/// assert_eq!(collapse_details_with(input, ">"), "> hi\n");
+/// ```
+#[must_use]
+pub fn collapse_details_with(input: &str, marker: &str) -> String {
     let normalised = normalize_line_endings(input);
     let dom = parse_document(RcDom::default(), ParseOpts::default()).one(normalised.as_ref());
     let mut out = String::new();
     for child in dom.document.children.borrow().iter() {
-        collapse_node(child, &mut out, false);
+        collapse_node(child, &mut out, false, marker);
+    }
+    out
+}
+
+/// Escape `text` for use in HTML content or a quoted attribute value.
+///
+/// # Examples
+///
+/// ```
+/// use vk::html::escape_html;
+/// assert_eq!(escape_html("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+/// ```
+#[must_use]
+pub fn escape_html(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len() + 16);
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
     }
+    Cow::Owned(out)
+}
+
+/// Reduce untrusted HTML to a safe subset.
+///
+/// Formatting, list, table and `<details>` elements are kept with a few
+/// harmless attributes. Scripts, styles, frames and forms are removed with
+/// their content, and other elements are replaced by their content. Links
+/// keep only `http`, `https`, `mailto` and in-page targets. Images become
+/// links to their source so the result never loads external assets.
+///
+/// # Examples
+///
+/// ```
+/// use vk::html::sanitize_html;
+/// let html = "<p onclick=\"x()\">hi<script>alert(1)</script></p>";
+/// assert_eq!(sanitize_html(html), "<p>hi</p>");
+/// ```
+#[must_use]
+pub fn sanitize_html(input: &str) -> String {
+    let dom = parse_document(RcDom::default(), ParseOpts::default()).one(input);
+    let mut out = String::new();
+    sanitize_node(&dom.document, &mut out);
     out
 }
 
-fn collapse_node(node: &Handle, out: &mut String, in_details: bool) {
+fn sanitize_node(node: &Handle, out: &mut String) {
+    match &node.data {
+        NodeData::Document => sanitize_children(node, out),
+        NodeData::Text { contents } => out.push_str(&escape_html(&contents.borrow())),
+        NodeData::Element { name, attrs, .. } => {
+            let tag = name.local.as_ref();
+            if DROPPED_ELEMENTS.contains(&tag) {
+                return;
+            }
+            let attrs = attrs.borrow();
+            let attr = |wanted: &str| {
+                attrs
+                    .iter()
+                    .find(|a| a.name.local.as_ref() == wanted)
+                    .map(|a| a.value.to_string())
+            };
+            if tag == "img" {
+                write_image_link(attr("src"), attr("alt"), out);
+                return;
+            }
+            if !ALLOWED_ELEMENTS.contains(&tag) {
+                sanitize_children(node, out);
+                return;
+            }
+            out.push('<');
+            out.push_str(tag);
+            for (_, name) in ALLOWED_ATTRIBUTES.iter().filter(|(t, _)| *t == tag) {
+                let Some(value) = attr(name) else { continue };
+                if *name == "href" && !is_safe_url(&value) {
+                    continue;
+                }
+                let _ = write!(out, " {name}=\"{}\"", escape_html(&value));
+            }
+            if tag == "a" {
+                out.push_str(" rel=\"nofollow noopener noreferrer\"");
+            }
+            out.push('>');
+            if VOID_ELEMENTS.contains(&tag) {
+                return;
+            }
+            sanitize_children(node, out);
+            let _ = write!(out, "</{tag}>");
+        }
+        _ => {}
+    }
+}
+
+fn sanitize_children(node: &Handle, out: &mut String) {
+    for child in node.children.borrow().iter() {
+        sanitize_node(child, out);
+    }
+}
+
+/// Replace an image with a link to `src` labelled by its `alt` text.
+fn write_image_link(src: Option<String>, alt: Option<String>, out: &mut String) {
+    let label = alt.filter(|a| !a.trim().is_empty());
+    let label = escape_html(label.as_deref().unwrap_or("image")).into_owned();
+    match src.filter(|s| is_safe_url(s)) {
+        Some(src) => {
+            let _ = write!(
+                out,
+                "<a href=\"{}\" rel=\"nofollow noopener noreferrer\">{label}</a>",
+                escape_html(&src)
+            );
+        }
+        None => out.push_str(&label),
+    }
+}
+
+/// Whether `url` is an in-page anchor or uses one of [`SAFE_SCHEMES`].
+fn is_safe_url(url: &str) -> bool {
+    let url = url.trim_start();
+    url.starts_with('#')
+        || SAFE_SCHEMES.iter().any(|scheme| {
+            url.get(..scheme.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+        })
+}
+
+fn collapse_node(node: &Handle, out: &mut String, in_details: bool, marker: &str) {
     match &node.data {
         NodeData::Element { name, .. }
             if name.local.eq_str_ignore_ascii_case("details")
                 && should_collapse_details(node, in_details) =>
         {
-            write_collapsed_summary(node, out);
+            write_collapsed_summary(node, out, marker);
             // drop children entirely when collapsing
         }
         NodeData::Element { name, .. } if name.local.eq_str_ignore_ascii_case("details") => {}
         NodeData::Element { .. } => {
             for child in node.children.borrow().iter() {
-                collapse_node(child, out, in_details);
+                collapse_node(child, out, in_details, marker);
             }
         }
         NodeData::Text { contents } if !in_details => {
@@ -79,9 +302,9 @@ fn should_collapse_details(node: &Handle, in_details: bool) -> bool {
     !in_details && find_summary_text(node).is_some()
 }
 
-fn write_collapsed_summary(node: &Handle, out: &mut String) {
+fn write_collapsed_summary(node: &Handle, out: &mut String, marker: &str) {
     if let Some(summary) = find_summary_text(node) {
-        out.push('\u{25B6}');
+        out.push_str(marker);
         out.push(' ');
         out.push_str(&summary);
         out.push('\n');
@@ -162,6 +385,43 @@ mod tests {
         assert_eq!(collapse_details(input), "\u{25B6} one\n\u{25B6} two\n");
     }
 
+    #[test]
+    fn custom_marker_prefixes_summary() {
+        let input = "<details><summary>one</summary>a</details>";
+        assert_eq!(collapse_details_with(input, ">"), "> one\n");
+    }
+
+    #[test]
+    fn sanitize_keeps_formatting_and_drops_scripts() {
+        let input = concat!(
+            "<p style=\"x\"><b>bold</b> <unknown>kept</unknown>",
+            "<style>p{}</style><script>alert(1)</script></p>"
+        );
+        assert_eq!(sanitize_html(input), "<p><b>bold</b> kept</p>");
+    }
+
+    #[test]
+    fn sanitize_filters_link_targets() {
+        let input =
+            "<a href=\"javascript:alert(1)\">x</a><a href=\"HTTPS://e.test/?a=1&b=2\">y</a>";
+        assert_eq!(
+            sanitize_html(input),
+            concat!(
+                "<a rel=\"nofollow noopener noreferrer\">x</a>",
+                "<a href=\"HTTPS://e.test/?a=1&amp;b=2\" rel=\"nofollow noopener noreferrer\">y</a>"
+            )
+        );
+    }
+
+    #[test]
+    fn sanitize_turns_images_into_links() {
+        let input = "<img src=\"https://e.test/a.png\" alt=\"a<b\"><img src=\"data:x\">";
+        assert_eq!(
+            sanitize_html(input),
+            "<a href=\"https://e.test/a.png\" rel=\"nofollow noopener noreferrer\">a&lt;b</a>image"
+        );
+    }
+
     #[test]
     fn normalize_line_endings_replaces_bare_carriage_returns() {
         let input = "line1\rline2\r\nline3";
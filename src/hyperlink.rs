@@ -0,0 +1,202 @@
+//! OSC 8 terminal hyperlinks.
+//!
+//! Terminals that understand OSC 8 show text wrapped in the escape sequence
+//! as a clickable link. There is no reliable way to ask a terminal whether it
+//! supports them, so support is inferred from the variables terminal
+//! emulators set, as other command-line tools do; `--hyperlinks` and the
+//! `hyperlinks` configuration key override the guess.
+
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use crate::ReviewComment;
+use crate::cli_args::HyperlinkMode;
+use crate::environment;
+use crate::worktree::LocalLine;
+
+/// Base URL of GitHub's web interface.
+const GITHUB_WEB: &str = "https://github.com";
+
+/// `TERM_PROGRAM` values of terminals known to support OSC 8.
+const TERM_PROGRAMS: &[&str] = &[
+    "ghostty",
+    "Hyper",
+    "iTerm.app",
+    "terminology",
+    "vscode",
+    "WezTerm",
+];
+
+/// `TERM` prefixes of terminals known to support OSC 8.
+const TERMS: &[&str] = &[
+    "alacritty",
+    "foot",
+    "wezterm",
+    "xterm-ghostty",
+    "xterm-kitty",
+];
+
+/// Wrap `text` in an OSC 8 hyperlink to `url`.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(osc8("https://x.test", "x"), "\x1b]8;;https://x.test\x1b\\x\x1b]8;;\x1b\\");
+/// ```
+#[must_use]
+pub fn osc8(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// Guess whether the terminal supports OSC 8 from environment variables
+/// looked up with `var`.
+#[must_use]
+pub fn terminal_supports_hyperlinks(var: impl Fn(&str) -> Option<String>) -> bool {
+    if var("TERM").as_deref() == Some("dumb") {
+        return false;
+    }
+    if ["DOMTERM", "WT_SESSION", "KONSOLE_VERSION"]
+        .iter()
+        .any(|name| var(name).is_some())
+    {
+        return true;
+    }
+    if var("VTE_VERSION")
+        .and_then(|v| v.parse::<u32>().ok())
+        .is_some_and(|v| v >= 5000)
+    {
+        return true;
+    }
+    if var("TERM_PROGRAM").is_some_and(|p| TERM_PROGRAMS.contains(&p.as_str())) {
+        return true;
+    }
+    var("TERM").is_some_and(|term| TERMS.iter().any(|t| term.starts_with(t)))
+}
+
+/// Decide whether to emit hyperlinks under `mode`.
+///
+/// `auto` links only on a terminal that appears to support OSC 8.
+#[must_use]
+pub fn hyperlinks_enabled(mode: HyperlinkMode, stdout_is_terminal: bool) -> bool {
+    match mode {
+        HyperlinkMode::Always => true,
+        HyperlinkMode::Never => false,
+        HyperlinkMode::Auto => {
+            stdout_is_terminal && terminal_supports_hyperlinks(|name| environment::var(name).ok())
+        }
+    }
+}
+
+/// Where author logins and file paths link to.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LinkTargets {
+    /// GitHub URL of the pull request head tree, ending in `/`, used for
+    /// file paths.
+    pub head_tree: Option<String>,
+    /// Root of the local checkout, used for file paths of comments mapped
+    /// into it with `--map-local`.
+    pub local_root: Option<PathBuf>,
+}
+
+impl LinkTargets {
+    /// Link file paths to `repo` at commit `oid` on GitHub.
+    #[must_use]
+    pub fn at_head(owner: &str, name: &str, oid: &str) -> Self {
+        Self {
+            head_tree: Some(format!("{GITHUB_WEB}/{owner}/{name}/blob/{oid}/")),
+            local_root: None,
+        }
+    }
+
+    /// Link file paths of mapped comments to the checkout at `root`.
+    #[must_use]
+    pub fn with_local_root(mut self, root: Option<PathBuf>) -> Self {
+        self.local_root = root;
+        self
+    }
+
+    /// GitHub profile URL for `login`.
+    #[must_use]
+    pub fn profile(login: &str) -> String {
+        format!("{GITHUB_WEB}/{}", encode_path(login))
+    }
+
+    /// URL of the file `comment` refers to, if one is known.
+    ///
+    /// Comments still present in the local checkout link to the local file;
+    /// others link to the file at the pull request head.
+    #[must_use]
+    pub fn file(&self, comment: &ReviewComment) -> Option<String> {
+        if let Some(root) = &self.local_root
+            && comment.local_line.and_then(LocalLine::line).is_some()
+        {
+            let path = root.join(&comment.path);
+            return Some(format!("file://{}", encode_path(&path.to_string_lossy())));
+        }
+        self.head_tree
+            .as_ref()
+            .map(|base| format!("{base}{}", encode_path(&comment.path)))
+    }
+}
+
+/// Percent-encode `path` for use in a URL, leaving `/` separators intact.
+fn encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            out.push(char::from(byte));
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[("TERM_PROGRAM", "iTerm.app")], true)]
+    #[case(&[("TERM", "xterm-kitty")], true)]
+    #[case(&[("VTE_VERSION", "7600")], true)]
+    #[case(&[("VTE_VERSION", "4800")], false)]
+    #[case(&[("WT_SESSION", "1")], true)]
+    #[case(&[("TERM", "dumb"), ("WT_SESSION", "1")], false)]
+    #[case(&[("TERM", "xterm-256color")], false)]
+    fn detects_supporting_terminals(#[case] env: &[(&str, &str)], #[case] expected: bool) {
+        let var = |name: &str| {
+            env.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| (*v).to_owned())
+        };
+        assert_eq!(terminal_supports_hyperlinks(var), expected);
+    }
+
+    #[test]
+    fn mode_overrides_detection() {
+        assert!(hyperlinks_enabled(HyperlinkMode::Always, false));
+        assert!(!hyperlinks_enabled(HyperlinkMode::Never, true));
+        assert!(!hyperlinks_enabled(HyperlinkMode::Auto, false));
+    }
+
+    #[test]
+    fn files_link_locally_when_mapped() {
+        let mut comment = ReviewComment {
+            path: "src/my file.rs".into(),
+            ..Default::default()
+        };
+        let targets =
+            LinkTargets::at_head("o", "r", "abc").with_local_root(Some(PathBuf::from("/work/r")));
+        assert_eq!(
+            targets.file(&comment).as_deref(),
+            Some("https://github.com/o/r/blob/abc/src/my%20file.rs")
+        );
+        comment.local_line = Some(LocalLine::Present { line: 3 });
+        assert_eq!(
+            targets.file(&comment).as_deref(),
+            Some("file:///work/r/src/my%20file.rs")
+        );
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! Defining the literals here keeps the renderer, its unit tests, and the
 //! CLI integration tests agreed on the exact code points, so a change to
-//! an icon only needs to be made in one place.
+//! an icon only needs to be made in one place. [`Icons`] groups them with
+//! the plain ASCII replacements used when the locale is not UTF-8.
 
 /// Globe glyph (U+1F30D) prefixing a comment permalink.
 pub const ICON_PERMALINK: &str = "\u{1f30d}";
@@ -15,3 +16,69 @@ pub const ICON_COMMENT: &str = "\u{1f4ac}";
 
 /// Memo glyph (U+1F4DD) prefixing a review author banner.
 pub const ICON_REVIEW: &str = "\u{1f4dd}";
+
+/// Triangle (U+25B6) marking a collapsed `<details>` block.
+pub const ICON_COLLAPSED: &str = "\u{25b6}";
+
+/// Check-mark glyph (U+2705) prefixing a thread's resolution note.
+pub const ICON_RESOLVED: &str = "\u{2705}";
+
+/// Glyphs used in printed output.
+///
+/// [`Icons::EMOJI`] uses the constants above; [`Icons::ASCII`] replaces them
+/// with plain text for terminals and logs that cannot display Unicode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Icons {
+    /// Prefix of a comment permalink.
+    pub permalink: &'static str,
+    /// Prefix of a file path.
+    pub file: &'static str,
+    /// Prefix of a comment author banner.
+    pub comment: &'static str,
+    /// Prefix of a review author banner.
+    pub review: &'static str,
+    /// Marker of a collapsed `<details>` block.
+    pub collapsed: &'static str,
+    /// Prefix of a thread's resolution note.
+    pub resolved: &'static str,
+}
+
+impl Icons {
+    /// Emoji glyphs for UTF-8 terminals.
+    pub const EMOJI: Self = Self {
+        permalink: ICON_PERMALINK,
+        file: ICON_FILE,
+        comment: ICON_COMMENT,
+        review: ICON_REVIEW,
+        collapsed: ICON_COLLAPSED,
+        resolved: ICON_RESOLVED,
+    };
+
+    /// Plain ASCII replacements.
+    pub const ASCII: Self = Self {
+        permalink: "[link]",
+        file: "[file]",
+        comment: "[comment]",
+        review: "[review]",
+        collapsed: ">",
+        resolved: "[resolved]",
+    };
+
+    /// Select the ASCII set when `ascii` is true, emoji otherwise.
+    #[must_use]
+    pub const fn select(ascii: bool) -> Self {
+        if ascii { Self::ASCII } else { Self::EMOJI }
+    }
+
+    /// Whether this is the ASCII set.
+    #[must_use]
+    pub fn is_ascii(self) -> bool {
+        self == Self::ASCII
+    }
+}
+
+impl Default for Icons {
+    fn default() -> Self {
+        Self::EMOJI
+    }
+}
@@ -11,26 +11,40 @@ mod cli_args;
 mod commands;
 // configuration helpers have been folded into `ortho_config`
 mod auth;
+mod author_filter;
 mod branch_pr;
 mod config_loader;
 mod diff;
+mod file_filter;
 mod graphql_queries;
-mod html;
+mod head_context;
+mod highlight;
+mod hyperlink;
 mod issues;
 #[cfg(test)]
 mod main_tests;
+mod pager;
 mod printer;
 mod ref_parser;
 mod resolve;
 mod review_threads;
 mod reviews;
+mod suggestion;
 mod summary;
 #[cfg(test)]
 mod test_utils;
+mod theme;
+mod thread_order;
+mod thread_search;
+mod time_filter;
+mod timestamps;
+mod worktree;
 
 mod environment {
     //! Environment helpers for the binary crate.
     pub(crate) use vk::environment::var;
+    #[cfg(unix)]
+    pub(crate) use vk::environment::var_os;
 }
 
 pub use crate::api::{GraphQLClient, paginate};
@@ -41,7 +55,7 @@ pub use review_threads::{
     filter_threads_by_files,
 };
 
-use crate::cli_args::{GlobalArgs, IssueArgs, PrArgs, ResolveArgs};
+use crate::cli_args::{ApplyArgs, GlobalArgs, IssueArgs, PrArgs, ResolveArgs};
 use clap::{Parser, Subcommand};
 use ortho_config::SubcmdConfigMerge;
 use regex::Regex;
@@ -51,9 +65,13 @@ use std::sync::LazyLock;
 use thiserror::Error;
 
 pub use auth::resolve_github_token;
-use commands::{run_issue, run_pr, run_resolve};
+use commands::{run_apply, run_issue, run_pr, run_resolve};
 
 #[derive(Subcommand, Deserialize, Serialize, Clone, Debug)]
+#[expect(
+    clippy::large_enum_variant,
+    reason = "parsed once at start-up; boxing would only complicate clap derive"
+)]
 enum Commands {
     /// Show unresolved pull request comments
     ///
@@ -70,6 +88,10 @@ enum Commands {
     ///
     /// The reference must include a fragment of the form `#discussion_r<ID>`
     Resolve(ResolveArgs),
+    /// Apply a comment's suggested change to the local file.
+    ///
+    /// The reference must include a fragment of the form `#discussion_r<ID>`
+    Apply(ApplyArgs),
 }
 
 #[derive(Debug, Parser)]
@@ -125,6 +147,10 @@ pub enum VkError {
     EmptyCommentPath { thread_id: Box<str>, index: usize },
     #[error("comment {comment_id} not found")]
     CommentNotFound { comment_id: u64 },
+    #[error("comment {comment_id} has no suggestion")]
+    NoSuggestion { comment_id: u64 },
+    #[error("cannot apply suggestion to {path}: {reason}")]
+    SuggestionConflict { path: Box<str>, reason: Box<str> },
     #[error("no pull request found for branch '{branch}'")]
     NoPrForBranch { branch: Box<str> },
     #[error("bad response: {0}")]
@@ -147,6 +173,18 @@ pub enum VkError {
     Io(#[from] Box<std::io::Error>),
     #[error("configuration error: {0}")]
     Config(#[from] SharedConfigError),
+    #[error("template '{name}' not found")]
+    TemplateNotFound { name: Box<str> },
+    #[error("template error: {0}")]
+    Template(Box<str>),
+    #[error("invalid file pattern '{pattern}': {reason}")]
+    InvalidFilePattern { pattern: Box<str>, reason: Box<str> },
+    #[error("invalid search pattern '{pattern}': {reason}")]
+    InvalidSearchPattern { pattern: Box<str>, reason: Box<str> },
+    #[error("invalid time '{value}': expected a duration such as 2d, a date, or a commit SHA")]
+    InvalidTime { value: Box<str> },
+    #[error("commit '{sha}' not found")]
+    CommitNotFound { sha: Box<str> },
 }
 
 /// Implement `From<$source>` for `VkError` by boxing the source into `$variant`.
@@ -201,6 +239,10 @@ async fn main() -> Result<(), VkError> {
                 let args = resolve_cli.load_and_merge()?;
                 run_resolve(args, &global, cli_token.as_deref()).await
             }
+            Commands::Apply(apply_cli) => {
+                let args = apply_cli.load_and_merge()?;
+                run_apply(args, &global, cli_token.as_deref()).await
+            }
         }
     }
     .await;
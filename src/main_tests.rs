@@ -1,7 +1,8 @@
 //! Tests for the `vk` binary entry point and top-level rendering behaviour.
 
 use super::*;
-use crate::printer::{write_comment_body, write_review, write_thread};
+use crate::cli_args::{DiffContext, OutputFormat};
+use crate::printer::{RenderOptions, write_comment_body, write_review, write_thread};
 use crate::reviews::PullRequestReview;
 use crate::test_utils::{EnvGuard, invalid_http_timeout_guard};
 use chrono::Utc;
@@ -144,6 +145,29 @@ fn pr_subcommand_parses_files() {
     assert_eq!(args.files, ["src/lib.rs", "README.md"]);
 }
 
+#[test]
+fn pr_subcommand_parses_repeated_author_filters() {
+    let args = parse_pr_args(&[
+        "vk",
+        "pr",
+        "123",
+        "--author",
+        "alice",
+        "--author",
+        "bob",
+        "--exclude-author",
+        "carol",
+        "--no-bots",
+        "--bot",
+        "renovate",
+    ]);
+    assert_eq!(args.authors, ["alice", "bob"]);
+    assert_eq!(args.exclude_authors, ["carol"]);
+    assert!(args.no_bots);
+    assert_eq!(args.bots, ["renovate"]);
+    assert!(args.files.is_empty());
+}
+
 #[test]
 fn pr_subcommand_parses_without_reference() {
     let args = parse_pr_args(&["vk", "pr"]);
@@ -188,6 +212,18 @@ fn resolve_subcommand_parses(#[case] argv: &[&str], #[case] expected_message: Op
     assert_eq!(args.message.as_deref(), expected_message);
 }
 
+#[rstest]
+#[case(&["vk", "apply", "83#discussion_r1"], false)]
+#[case(&["vk", "apply", "83#discussion_r1", "--dry-run"], true)]
+fn apply_subcommand_parses(#[case] argv: &[&str], #[case] dry_run: bool) {
+    let cli = Cli::try_parse_from(argv).expect("parse cli");
+    let Commands::Apply(args) = cli.command else {
+        panic!("expected Apply command, got different variant");
+    };
+    assert_eq!(args.reference, "83#discussion_r1");
+    assert_eq!(args.dry_run, dry_run);
+}
+
 #[test]
 fn version_flag_displays_version() {
     let err = Cli::try_parse_from(["vk", "--version"]).expect_err("display version");
@@ -217,7 +253,7 @@ fn write_thread_emits_diff_once() {
     };
     let skin = MadSkin::default();
     let mut buf = Vec::new();
-    write_thread(&mut buf, &skin, &thread).expect("write thread");
+    write_thread(&mut buf, &skin, &thread, &RenderOptions::default()).expect("write thread");
     let out = String::from_utf8(buf).expect("utf8");
     assert_eq!(out.matches("|-old").count(), 1);
     assert_eq!(out.matches("wrote:").count(), 2);
@@ -231,7 +267,8 @@ fn comment_body_collapses_details() {
     };
     let skin = MadSkin::default();
     let mut buf = Vec::new();
-    write_comment_body(&mut buf, &skin, &comment).expect("write comment");
+    write_comment_body(&mut buf, &skin, &comment, &RenderOptions::default())
+        .expect("write comment");
     let out = String::from_utf8(buf).expect("utf8");
     assert!(out.contains("\u{25B6} note"));
     assert!(!out.contains("hidden"));
@@ -247,8 +284,37 @@ fn review_body_collapses_details() {
     };
     let skin = MadSkin::default();
     let mut buf = Vec::new();
-    write_review(&mut buf, &skin, &review).expect("write review");
+    write_review(&mut buf, &skin, &review, &RenderOptions::default()).expect("write review");
     let out = String::from_utf8(buf).expect("utf8");
     assert!(out.contains("\u{25B6} hello"));
     assert!(!out.contains("bye"));
 }
+
+#[rstest]
+#[case(None, true, OutputFormat::Text)]
+#[case(None, false, OutputFormat::Markdown)]
+#[case(Some(OutputFormat::Text), false, OutputFormat::Text)]
+#[case(Some(OutputFormat::Json), true, OutputFormat::Json)]
+fn output_format_defaults_follow_terminal(
+    #[case] requested: Option<OutputFormat>,
+    #[case] is_terminal: bool,
+    #[case] expected: OutputFormat,
+) {
+    assert_eq!(OutputFormat::resolve(requested, is_terminal), expected);
+}
+
+#[rstest]
+#[case(serde_json::json!(12), DiffContext::Lines(12))]
+#[case(serde_json::json!("full"), DiffContext::Full)]
+#[case(serde_json::json!("3"), DiffContext::Lines(3))]
+fn diff_context_deserialises_from_config(
+    #[case] value: serde_json::Value,
+    #[case] expected: DiffContext,
+) {
+    let context: DiffContext = serde_json::from_value(value.clone()).expect("deserialise");
+    assert_eq!(context, expected);
+    let round_trip: DiffContext =
+        serde_json::from_value(serde_json::to_value(context).expect("serialise"))
+            .expect("round trip");
+    assert_eq!(round_trip, expected);
+}
@@ -0,0 +1,223 @@
+//! Page long terminal output.
+//!
+//! When standard output is a terminal, `vk` starts a pager and points its own
+//! standard output at the pager's input, so every existing `stdout()` writer
+//! is paged without being threaded through a new handle. The pager command is
+//! read from `VK_PAGER`, then `PAGER`, and defaults to `less -FRX`, which
+//! exits at once when the output fits on one screen. An empty command or
+//! `cat` disables paging. A pager program that cannot be found on `PATH` is
+//! reported and output goes straight to the terminal, rather than into a
+//! shell that exits at once.
+//!
+//! Quitting the pager early closes the pipe, so later writes fail with
+//! `BrokenPipe` and the existing broken-pipe handling stops output quietly.
+//! Paging is only available on Unix.
+
+use crate::environment;
+#[cfg(unix)]
+use std::{ffi::OsStr, path::Path};
+
+/// Pager used when neither `VK_PAGER` nor `PAGER` is set.
+pub const DEFAULT_PAGER: &str = "less -FRX";
+
+/// Choose the pager command from `vk_pager` and `pager`, in that order.
+///
+/// Returns `None` when the chosen command is empty or `cat`, which means
+/// paging is disabled.
+#[must_use]
+pub fn pager_command(vk_pager: Option<&str>, pager: Option<&str>) -> Option<String> {
+    let command = vk_pager.or(pager).unwrap_or(DEFAULT_PAGER).trim();
+    (!command.is_empty() && command != "cat").then(|| command.to_owned())
+}
+
+/// The program a pager `command` runs, skipping leading variable assignments
+/// such as `LESS=-R less`.
+///
+/// Returns `None` when the command relies on shell syntax such as quoting or
+/// expansion, so the program cannot be known without running the shell.
+#[cfg(unix)]
+fn pager_program(command: &str) -> Option<&str> {
+    let program = command.split_whitespace().find(|word| {
+        !word
+            .split_once('=')
+            .is_some_and(|(name, _)| !name.is_empty() && !name.contains('/'))
+    })?;
+    (!program.contains(['"', '\'', '\\', '$', '`', '(', '~'])).then_some(program)
+}
+
+/// Whether `program` names an executable file, directly or through the
+/// directories in `path`.
+#[cfg(unix)]
+fn resolves(program: &str, path: Option<&OsStr>) -> bool {
+    if program.contains('/') {
+        return is_executable(Path::new(program));
+    }
+    path.is_some_and(|path| {
+        std::env::split_paths(path).any(|dir| is_executable(&dir.join(program)))
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+/// A running pager receiving this process's standard output.
+///
+/// Dropping the guard flushes standard output, restores the original
+/// descriptor so the pager sees end of input, and waits for the user to quit
+/// the pager.
+pub struct Pager {
+    #[cfg(unix)]
+    child: std::process::Child,
+    #[cfg(unix)]
+    saved_stdout: std::os::fd::OwnedFd,
+}
+
+impl Pager {
+    /// Start the configured pager when `stdout_is_terminal` is true.
+    ///
+    /// Returns `None` when paging is disabled, unsupported on this platform,
+    /// or the pager cannot be found or started; output then goes straight to
+    /// the terminal.
+    #[must_use]
+    pub fn start(stdout_is_terminal: bool) -> Option<Self> {
+        if !stdout_is_terminal {
+            return None;
+        }
+        let command = pager_command(
+            environment::var("VK_PAGER").ok().as_deref(),
+            environment::var("PAGER").ok().as_deref(),
+        )?;
+        Self::spawn(&command)
+    }
+
+    #[cfg(unix)]
+    fn spawn(command: &str) -> Option<Self> {
+        use std::io::Write;
+        use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+        use std::process::{Command, Stdio};
+
+        if let Some(program) = pager_program(command)
+            && !resolves(program, environment::var_os("PATH").as_deref())
+        {
+            tracing::warn!("pager `{program}` not found; writing to the terminal");
+            return None;
+        }
+        let mut child = match Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                tracing::warn!("could not start pager `{command}`: {e}");
+                return None;
+            }
+        };
+        let input = OwnedFd::from(child.stdin.take()?);
+        let _ = std::io::stdout().flush();
+        let stdout_fd = std::io::stdout().as_raw_fd();
+        // SAFETY: `dup` has no memory-safety preconditions; the result is
+        // checked before it is wrapped.
+        let saved = unsafe { libc::dup(stdout_fd) };
+        if saved < 0 {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        // SAFETY: `saved` is a freshly duplicated descriptor owned by nothing
+        // else.
+        let saved_stdout = unsafe { OwnedFd::from_raw_fd(saved) };
+        // SAFETY: both descriptors are open; `dup2` replaces standard output
+        // with the pager's input and leaves `input` to be closed on drop.
+        if unsafe { libc::dup2(input.as_raw_fd(), stdout_fd) } < 0 {
+            let _ = child.kill();
+            let _ = child.wait();
+            return None;
+        }
+        Some(Self {
+            child,
+            saved_stdout,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn spawn(_command: &str) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Pager {
+    fn drop(&mut self) {
+        use std::io::Write;
+        use std::os::fd::AsRawFd;
+
+        let _ = std::io::stdout().flush();
+        // SAFETY: both descriptors are open; restoring standard output closes
+        // the last write end of the pager's input.
+        unsafe { libc::dup2(self.saved_stdout.as_raw_fd(), std::io::stdout().as_raw_fd()) };
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Some("most"), Some("more"), Some("most"))]
+    #[case(None, Some("more"), Some("more"))]
+    #[case(None, None, Some(DEFAULT_PAGER))]
+    #[case(Some(""), Some("more"), None)]
+    #[case(None, Some("cat"), None)]
+    fn pager_command_precedence(
+        #[case] vk_pager: Option<&str>,
+        #[case] pager: Option<&str>,
+        #[case] expected: Option<&str>,
+    ) {
+        assert_eq!(pager_command(vk_pager, pager).as_deref(), expected);
+    }
+
+    #[cfg(unix)]
+    #[rstest]
+    #[case("less -FRX", Some("less"))]
+    #[case("LESS=-R less", Some("less"))]
+    #[case("  /usr/bin/most", Some("/usr/bin/most"))]
+    #[case("\"$HOME/bin/pager\"", None)]
+    #[case("LESS=-R", None)]
+    fn pager_program_skips_assignments(#[case] command: &str, #[case] expected: Option<&str>) {
+        assert_eq!(pager_program(command), expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pager_programs_are_resolved_on_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let pager = dir.path().join("pager");
+        std::fs::write(&pager, "#!/bin/sh\ncat\n").expect("write pager");
+        std::fs::set_permissions(&pager, std::fs::Permissions::from_mode(0o755))
+            .expect("make pager executable");
+        std::fs::write(dir.path().join("notes"), "").expect("write notes");
+        let path = dir.path().as_os_str();
+
+        assert!(resolves("pager", Some(path)));
+        assert!(resolves(pager.to_str().expect("utf-8 path"), None));
+        assert!(!resolves("notes", Some(path)));
+        assert!(!resolves("missing-pager", Some(path)));
+        assert!(!resolves("pager", None));
+    }
+
+    #[test]
+    fn non_terminals_are_not_paged() {
+        assert!(Pager::start(false).is_none());
+    }
+}
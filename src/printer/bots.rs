@@ -0,0 +1,339 @@
+//! Extract the actionable parts of comments left by review bots.
+//!
+//! Review bots wrap their findings in walkthroughs, tool output,
+//! and disclaimers folded into `<details>` blocks. Collapsing every block
+//! hides the suggested fix and the prompt meant for coding agents along with
+//! the noise, so each known bot gets an extractor that keeps the summary text
+//! and the useful blocks and drops the rest. Extractors are looked up by
+//! author login; comments from anyone else use the generic rendering.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::ReviewComment;
+use crate::suggestion::render_suggestions_in;
+use vk::icons::Icons;
+
+use super::collapse_excessive_newlines;
+
+static HTML_COMMENT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").expect("valid regex"));
+static DETAILS_TAG_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<(/?)details\b[^>]*>").expect("valid regex"));
+static SUMMARY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<summary\b[^>]*>(.*?)</summary>").expect("valid regex"));
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]+>").expect("valid regex"));
+/// Emoji bots use as labels, with their variation selectors, joiners and
+/// one following space.
+static EMOJI_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\p{Extended_Pictographic}[\x{FE0E}\x{FE0F}\x{200D}\x{20E3}]*)+ ?")
+        .expect("valid regex")
+});
+
+/// A useful `<details>` section in a bot comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// Code the bot proposes, usually a `suggestion` or `diff` block.
+    SuggestedFix,
+    /// Instructions written for coding agents.
+    AgentPrompt,
+}
+
+impl Section {
+    const fn heading(self) -> &'static str {
+        match self {
+            Self::SuggestedFix => "Suggested fix",
+            Self::AgentPrompt => "Prompt for AI agents",
+        }
+    }
+}
+
+/// Actionable content pulled out of a bot comment.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Extracted {
+    /// Text outside any `<details>` block, without HTML comments.
+    pub summary: String,
+    /// Kept sections in the order they appeared.
+    pub sections: Vec<(Section, String)>,
+}
+
+impl Extracted {
+    /// Render the extract as Markdown with a bold heading per section.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = self.summary.clone();
+        for (section, content) in &self.sections {
+            out.push_str("\n\n**");
+            out.push_str(section.heading());
+            out.push_str("**\n\n");
+            out.push_str(content);
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// Recognises one bot's comments and extracts their useful parts.
+///
+/// Implementations are registered in [`EXTRACTORS`].
+pub trait CommentExtractor: Sync {
+    /// Whether comments by `login` come from this bot.
+    fn matches(&self, login: &str) -> bool;
+    /// Pull the actionable parts out of `body`.
+    fn extract(&self, body: &str) -> Extracted;
+}
+
+/// Extractor for bots that fold extras into `<details>` blocks, keeping the
+/// blocks whose summary contains one of the listed markers.
+pub struct DetailsExtractor {
+    logins: &'static [&'static str],
+    keep: &'static [(&'static str, Section)],
+}
+
+impl CommentExtractor for DetailsExtractor {
+    fn matches(&self, login: &str) -> bool {
+        let login = login.strip_suffix("[bot]").unwrap_or(login);
+        self.logins.iter().any(|l| l.eq_ignore_ascii_case(login))
+    }
+
+    fn extract(&self, body: &str) -> Extracted {
+        let body = HTML_COMMENT_RE.replace_all(body, "");
+        let mut summary = String::new();
+        let mut sections = Vec::new();
+        for block in split_details(&body) {
+            match block {
+                Block::Text(text) => summary.push_str(text),
+                Block::Details {
+                    summary: title,
+                    body,
+                } => {
+                    let kept = self
+                        .keep
+                        .iter()
+                        .find(|(marker, _)| title.to_lowercase().contains(marker));
+                    if let Some((_, section)) = kept {
+                        let content = first_fenced_block(body).unwrap_or_else(|| body.trim());
+                        sections.push((*section, content.to_owned()));
+                    }
+                }
+            }
+        }
+        Extracted {
+            summary: summary.trim().to_owned(),
+            sections,
+        }
+    }
+}
+
+/// Known review bots, checked in order.
+pub static EXTRACTORS: &[&dyn CommentExtractor] = &[
+    &DetailsExtractor {
+        logins: &["coderabbitai"],
+        keep: &[
+            ("committable suggestion", Section::SuggestedFix),
+            ("prompt for ai agents", Section::AgentPrompt),
+        ],
+    },
+    &DetailsExtractor {
+        logins: &["copilot-pull-request-reviewer", "copilot"],
+        keep: &[("suggested change", Section::SuggestedFix)],
+    },
+    &DetailsExtractor {
+        logins: &["sourcery-ai"],
+        keep: &[("prompt for ai agents", Section::AgentPrompt)],
+    },
+];
+
+/// Render a bot comment through its extractor, or return `None` when the
+/// author is not a known bot.
+///
+/// Suggestion blocks in the result are shown as diffs, as for any comment.
+/// With the ASCII `icons` the emoji bots use as labels are dropped.
+#[must_use]
+pub fn extract_bot_comment(comment: &ReviewComment, icons: Icons) -> Option<String> {
+    let login = comment.author.as_ref()?.login.as_str();
+    let extractor = EXTRACTORS.iter().find(|e| e.matches(login))?;
+    let markdown = extractor.extract(&comment.body).to_markdown();
+    let rendered = render_suggestions_in(&markdown, comment);
+    let rendered = if icons.is_ascii() {
+        EMOJI_RE.replace_all(&rendered, "").into_owned()
+    } else {
+        rendered.into_owned()
+    };
+    Some(collapse_excessive_newlines(rendered))
+}
+
+/// Part of a comment body at the top level.
+enum Block<'a> {
+    Text(&'a str),
+    Details { summary: String, body: &'a str },
+}
+
+/// Split `body` into text and top-level `<details>` blocks.
+///
+/// Nested blocks stay inside their parent's body. An unclosed block runs to
+/// the end of the text.
+fn split_details(body: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut depth = 0_usize;
+    let mut text_start = 0;
+    let mut open_end = 0;
+    for tag in DETAILS_TAG_RE.captures_iter(body) {
+        let (Some(whole), closing) = (tag.get(0), tag.get(1).is_some_and(|m| !m.is_empty())) else {
+            continue;
+        };
+        if !closing {
+            if depth == 0 {
+                blocks.push(Block::Text(
+                    body.get(text_start..whole.start()).unwrap_or_default(),
+                ));
+                open_end = whole.end();
+            }
+            depth += 1;
+        } else if depth > 0 {
+            depth -= 1;
+            if depth == 0 {
+                blocks.push(details_block(
+                    body.get(open_end..whole.start()).unwrap_or_default(),
+                ));
+                text_start = whole.end();
+            }
+        }
+    }
+    if depth > 0 {
+        blocks.push(details_block(body.get(open_end..).unwrap_or_default()));
+    } else {
+        blocks.push(Block::Text(body.get(text_start..).unwrap_or_default()));
+    }
+    blocks
+}
+
+/// Separate a `<details>` block's summary from the rest of its content.
+fn details_block(inner: &str) -> Block<'_> {
+    let Some(caps) = SUMMARY_RE.captures(inner) else {
+        return Block::Details {
+            summary: String::new(),
+            body: inner,
+        };
+    };
+    let (Some(whole), Some(title)) = (caps.get(0), caps.get(1)) else {
+        return Block::Details {
+            summary: String::new(),
+            body: inner,
+        };
+    };
+    Block::Details {
+        summary: TAG_RE.replace_all(title.as_str(), "").trim().to_owned(),
+        body: inner.get(whole.end()..).unwrap_or_default(),
+    }
+}
+
+/// Return the first fenced code block in `text`, fences included.
+fn first_fenced_block(text: &str) -> Option<&str> {
+    let mut offset = 0;
+    let mut open: Option<(usize, &str)> = None;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        match open {
+            None => {
+                let fence_len = trimmed
+                    .chars()
+                    .take_while(|c| *c == '`' || *c == '~')
+                    .count();
+                if fence_len >= 3 {
+                    open = Some((offset, trimmed.get(..fence_len).unwrap_or_default()));
+                }
+            }
+            Some((start, fence)) => {
+                if trimmed.len() >= fence.len() && trimmed.chars().all(|c| fence.starts_with(c)) {
+                    return text.get(start..offset + line.trim_end().len());
+                }
+            }
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::User;
+    use rstest::rstest;
+
+    const CODERABBIT_COMMENT: &str = include_str!("../../tests/fixtures/comment_coderabbit.txt");
+
+    fn comment(login: &str, body: &str) -> ReviewComment {
+        ReviewComment {
+            body: body.into(),
+            diff_hunk: "@@ -1 +1 @@\n+old".into(),
+            author: Some(User {
+                login: login.into(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case("coderabbitai", true)]
+    #[case("coderabbitai[bot]", true)]
+    #[case("Copilot", true)]
+    #[case("sourcery-ai[bot]", true)]
+    #[case("alice", false)]
+    fn bots_are_recognised_by_login(#[case] login: &str, #[case] known: bool) {
+        assert_eq!(EXTRACTORS.iter().any(|e| e.matches(login)), known);
+    }
+
+    #[test]
+    fn coderabbit_keeps_fix_and_prompt() {
+        let out = extract_bot_comment(&comment("coderabbitai", CODERABBIT_COMMENT), Icons::EMOJI)
+            .expect("known bot");
+        assert!(out.starts_with("_⚠️ Potential issue_ | _🟡 Minor_\n\n**Consolidate"));
+        assert!(out.contains("**Suggested fix**\n\n```diff\n-old\n+                printf"));
+        assert!(out.contains("**Prompt for AI agents**\n\n```\n.github/workflows/release.yml"));
+        assert!(out.contains("'<asset_name>'"));
+        assert!(out.contains("✅ Addressed in commit 70c0c7a"));
+        for boilerplate in ["IMPORTANT", "<!--", "<details>", "auto-generated"] {
+            assert!(!out.contains(boilerplate), "kept {boilerplate}: {out}");
+        }
+    }
+
+    #[test]
+    fn ascii_icons_drop_bot_emoji() {
+        let out = extract_bot_comment(&comment("coderabbitai", CODERABBIT_COMMENT), Icons::ASCII)
+            .expect("known bot");
+        assert!(
+            out.starts_with("_Potential issue_ | _Minor_\n\n**Consolidate"),
+            "{out}"
+        );
+        assert!(out.contains("Addressed in commit 70c0c7a"));
+        for emoji in ["⚠", "🟡", "✅", "\u{fe0f}"] {
+            assert!(!out.contains(emoji), "kept {emoji}: {out}");
+        }
+    }
+
+    #[test]
+    fn unknown_sections_and_nested_blocks_are_dropped() {
+        let body = "Fix it.\n<details><summary>🧰 Tools</summary>\n<details><summary>x</summary>y</details>\nlint\n</details>\nDone.";
+        let out =
+            extract_bot_comment(&comment("coderabbitai", body), Icons::EMOJI).expect("known bot");
+        assert_eq!(out, "Fix it.\n\nDone.\n");
+    }
+
+    #[test]
+    fn human_comments_are_not_extracted() {
+        assert_eq!(
+            extract_bot_comment(&comment("alice", CODERABBIT_COMMENT), Icons::EMOJI),
+            None
+        );
+    }
+
+    #[rstest]
+    #[case("a\n```rust\nx\n```\nb", Some("```rust\nx\n```"))]
+    #[case("````\n```\n````", Some("````\n```\n````"))]
+    #[case("```\nunclosed", None)]
+    fn fenced_blocks_are_found(#[case] text: &str, #[case] expected: Option<&str>) {
+        assert_eq!(first_fenced_block(text), expected);
+    }
+}
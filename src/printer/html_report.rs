@@ -0,0 +1,378 @@
+//! Standalone HTML report of a pull request's review state.
+//!
+//! The report is a single file with its styles inlined, so it can be mailed
+//! or attached to a ticket and opened without network access. Comment bodies
+//! are rendered from Markdown and then passed through
+//! [`sanitize_html`], because raw HTML in a comment is written by whoever
+//! posted it and must not run in the reader's browser. Nothing in the report
+//! loads an external asset: images in comments become links.
+
+use std::io::Write;
+
+use pulldown_cmark::{Options, Parser, html::push_html};
+
+use super::bots::extract_bot_comment;
+use super::{Formattable, banner_tail, reactions_line, resolution_note};
+use crate::ReviewThread;
+use crate::cli_args::{DiffContext, TimestampStyle};
+use crate::diff::{comment_line, format_comment_diff};
+use crate::ref_parser::RepoInfo;
+use crate::reviews::PullRequestReview;
+use crate::suggestion::render_suggestions;
+use crate::worktree::local_note;
+use vk::html::{escape_html, sanitize_html};
+use vk::icons::Icons;
+
+/// Styles inlined into every report.
+const STYLE: &str = "\
+:root { color-scheme: light dark; --muted: #6e7781; --line: #d0d7de;
+  --add: #dafbe1; --del: #ffebe9; --target: #fff8c5; }
+@media (prefers-color-scheme: dark) {
+  :root { --muted: #8b949e; --line: #30363d; --add: #103818; --del: #481418;
+    --target: #3b2e00; }
+}
+body { font: 15px/1.5 system-ui, sans-serif; max-width: 60rem; margin: 2rem auto;
+  padding: 0 1rem; }
+a { color: inherit; }
+table.summary { border-collapse: collapse; }
+table.summary th, table.summary td { border: 1px solid var(--line);
+  padding: 0.25rem 0.75rem; text-align: left; }
+table.summary td.count { text-align: right; }
+.review, .thread { border: 1px solid var(--line); border-radius: 6px;
+  margin: 1rem 0; padding: 0 1rem; }
+.comment + .comment { border-top: 1px solid var(--line); }
+.byline { font-weight: 600; }
+.byline .when, .note, .reactions { color: var(--muted); font-weight: normal; }
+pre { overflow-x: auto; }
+pre.diff { font-size: 13px; border: 1px solid var(--line); border-radius: 6px; }
+pre.diff span { display: block; padding: 0 0.5rem; }
+pre.diff .add { background: var(--add); }
+pre.diff .del { background: var(--del); }
+pre.diff .target { background: var(--target); }
+.body img { max-width: 100%; }
+";
+
+/// Options used to build the report.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HtmlOptions {
+    /// Diff lines shown around each commented line.
+    pub context: DiffContext,
+    /// Show comment bodies as posted instead of extracting bot comments.
+    pub raw_body: bool,
+    /// Glyphs used for reactions.
+    pub icons: Icons,
+    /// How posting times are shown.
+    pub timestamps: TimestampStyle,
+}
+
+/// Everything shown in the report.
+#[derive(Debug)]
+pub struct HtmlReport<'a> {
+    /// Repository the pull request belongs to.
+    pub repo: &'a RepoInfo,
+    /// Pull request number.
+    pub number: u64,
+    /// Per-file comment counts from [`crate::summary::summarize_files`].
+    pub summary: &'a [(String, usize)],
+    /// Latest review from each reviewer.
+    pub reviews: &'a [PullRequestReview],
+    /// Threads to show.
+    pub threads: &'a [ReviewThread],
+}
+
+/// Write `report` as a self-contained HTML document.
+///
+/// # Errors
+///
+/// Returns an error when writing to `out` fails or a diff cannot be
+/// formatted.
+pub fn write_html_report<W: Write>(
+    mut out: W,
+    report: &HtmlReport<'_>,
+    options: HtmlOptions,
+) -> anyhow::Result<()> {
+    let HtmlReport { repo, number, .. } = *report;
+    let title = escape_html(&format!("{}/{}#{number}", repo.owner, repo.name)).into_owned();
+    let url = format!(
+        "https://github.com/{}/{}/pull/{number}",
+        repo.owner, repo.name
+    );
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html lang=\"en\">")?;
+    writeln!(out, "<head>")?;
+    writeln!(out, "<meta charset=\"utf-8\">")?;
+    writeln!(
+        out,
+        "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">"
+    )?;
+    writeln!(out, "<title>Code review of {title}</title>")?;
+    writeln!(out, "<style>\n{STYLE}</style>")?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+    writeln!(
+        out,
+        "<h1>Code review of <a href=\"{}\">{title}</a></h1>",
+        escape_html(&url)
+    )?;
+    write_summary(&mut out, report.summary)?;
+    write_reviews(&mut out, report.reviews, options)?;
+    write_threads(&mut out, report.threads, options)?;
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")?;
+    Ok(())
+}
+
+fn write_summary<W: Write>(out: &mut W, summary: &[(String, usize)]) -> anyhow::Result<()> {
+    writeln!(out, "<h2>Summary</h2>")?;
+    if summary.is_empty() {
+        writeln!(out, "<p>No unresolved comments.</p>")?;
+        return Ok(());
+    }
+    writeln!(out, "<table class=\"summary\">")?;
+    writeln!(
+        out,
+        "<thead><tr><th>File</th><th>Comments</th></tr></thead>"
+    )?;
+    writeln!(out, "<tbody>")?;
+    for (path, count) in summary {
+        writeln!(
+            out,
+            "<tr><td><code>{}</code></td><td class=\"count\">{count}</td></tr>",
+            escape_html(path)
+        )?;
+    }
+    writeln!(out, "</tbody>")?;
+    writeln!(out, "</table>")?;
+    Ok(())
+}
+
+fn write_reviews<W: Write>(
+    out: &mut W,
+    reviews: &[PullRequestReview],
+    options: HtmlOptions,
+) -> anyhow::Result<()> {
+    if reviews.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "<h2>Reviews</h2>")?;
+    for review in reviews {
+        writeln!(out, "<section class=\"review\">")?;
+        write_byline(out, review, None, options.timestamps)?;
+        write_body(out, &review.body)?;
+        writeln!(out, "</section>")?;
+    }
+    Ok(())
+}
+
+fn write_threads<W: Write>(
+    out: &mut W,
+    threads: &[ReviewThread],
+    options: HtmlOptions,
+) -> anyhow::Result<()> {
+    if threads.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "<h2>Threads</h2>")?;
+    for thread in threads {
+        write_thread(out, thread, options)?;
+    }
+    Ok(())
+}
+
+/// Write one thread: its location, notes and diff, then every comment.
+fn write_thread<W: Write>(
+    out: &mut W,
+    thread: &ReviewThread,
+    options: HtmlOptions,
+) -> anyhow::Result<()> {
+    let Some(first) = thread.comments.nodes.first() else {
+        return Ok(());
+    };
+    let location = comment_line(first).map_or_else(
+        || first.path.clone(),
+        |line| format!("{}:{line}", first.path),
+    );
+    writeln!(out, "<article class=\"thread\">")?;
+    writeln!(
+        out,
+        "<h3><a href=\"{}\"><code>{}</code></a></h3>",
+        escape_html(&first.url),
+        escape_html(&location)
+    )?;
+    let notes = resolution_note(thread)
+        .into_iter()
+        .chain(local_note(first, thread.local_status));
+    for note in notes {
+        writeln!(out, "<p class=\"note\">{}</p>", escape_html(&note))?;
+    }
+    let diff = match &first.head_context {
+        Some(head) => head.format()?,
+        None => format_comment_diff(first, options.context)?,
+    };
+    write_diff(out, &diff)?;
+    for (i, comment) in thread.comments.nodes.iter().enumerate() {
+        writeln!(out, "<div class=\"comment\">")?;
+        if i > 0
+            && let Some(note) = local_note(comment, None)
+        {
+            writeln!(out, "<p class=\"note\">{}</p>", escape_html(&note))?;
+        }
+        write_byline(out, comment, Some(&comment.url), options.timestamps)?;
+        let body = if options.raw_body {
+            comment.body.clone()
+        } else {
+            extract_bot_comment(comment, options.icons)
+                .unwrap_or_else(|| render_suggestions(comment).into_owned())
+        };
+        write_body(out, &body)?;
+        if let Some(line) = reactions_line(&comment.reactions, options.icons) {
+            writeln!(out, "<p class=\"reactions\">{}</p>", escape_html(&line))?;
+        }
+        writeln!(out, "</div>")?;
+    }
+    writeln!(out, "</article>")?;
+    Ok(())
+}
+
+/// Write an author line such as `alice wrote 3 days ago:`, linking the
+/// author to `url` when given.
+fn write_byline<W: Write, T: Formattable>(
+    out: &mut W,
+    item: &T,
+    url: Option<&str>,
+    timestamps: TimestampStyle,
+) -> anyhow::Result<()> {
+    let login = escape_html(item.author_login().unwrap_or("(unknown)")).into_owned();
+    let author = url.map_or_else(
+        || login.clone(),
+        |url| format!("<a href=\"{}\">{login}</a>", escape_html(url)),
+    );
+    writeln!(
+        out,
+        "<p class=\"byline\">{author}<span class=\"when\">{}</span></p>",
+        escape_html(&banner_tail(item, timestamps))
+    )?;
+    Ok(())
+}
+
+/// Render the Markdown `body` and write it after sanitising the HTML.
+fn write_body<W: Write>(out: &mut W, body: &str) -> anyhow::Result<()> {
+    if body.trim().is_empty() {
+        return Ok(());
+    }
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let mut html = String::new();
+    push_html(&mut html, Parser::new_ext(body, options));
+    writeln!(out, "<div class=\"body\">{}</div>", sanitize_html(&html))?;
+    Ok(())
+}
+
+/// Write a formatted diff hunk, tinting added, removed and targeted lines.
+///
+/// Lines carry a `|`-separated gutter; the character after it marks the
+/// line's kind.
+fn write_diff<W: Write>(out: &mut W, diff: &str) -> anyhow::Result<()> {
+    if diff.is_empty() {
+        return Ok(());
+    }
+    write!(out, "<pre class=\"diff\"><code>")?;
+    for line in diff.lines() {
+        let marker = line
+            .split_once('|')
+            .and_then(|(_, text)| text.chars().next());
+        let class = match marker {
+            Some('+') => " class=\"add\"",
+            Some('-') => " class=\"del\"",
+            Some('>') => " class=\"target\"",
+            _ => "",
+        };
+        write!(out, "<span{class}>{}</span>", escape_html(line))?;
+    }
+    writeln!(out, "</code></pre>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summary::summarize_files;
+    use crate::{CommentConnection, ReviewComment, User};
+
+    fn render(threads: &[ReviewThread], reviews: &[PullRequestReview]) -> String {
+        let repo = RepoInfo {
+            owner: "o".into(),
+            name: "r".into(),
+        };
+        let summary = summarize_files(threads);
+        let report = HtmlReport {
+            repo: &repo,
+            number: 5,
+            summary: &summary,
+            reviews,
+            threads,
+        };
+        let mut buf = Vec::new();
+        write_html_report(&mut buf, &report, HtmlOptions::default()).expect("write report");
+        String::from_utf8(buf).expect("utf8")
+    }
+
+    fn thread(body: &str) -> ReviewThread {
+        ReviewThread {
+            comments: CommentConnection {
+                nodes: vec![ReviewComment {
+                    path: "src/a<b>.rs".into(),
+                    body: body.into(),
+                    diff_hunk: "@@ -1 +1 @@\n-old\n+new".into(),
+                    url: "https://github.com/o/r/pull/5#discussion_r1".into(),
+                    author: Some(User {
+                        login: "alice".into(),
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn report_renders_summary_reviews_and_threads() {
+        let review = PullRequestReview {
+            body: "Looks **good**".into(),
+            state: "APPROVED".into(),
+            author: Some(User {
+                login: "bob".into(),
+            }),
+            submitted_at: None,
+        };
+        let out = render(&[thread("Use `x`")], &[review]);
+        assert!(out.starts_with("<!DOCTYPE html>"));
+        assert!(out.contains("<title>Code review of o/r#5</title>"));
+        assert!(out.contains("<td><code>src/a&lt;b&gt;.rs</code></td><td class=\"count\">1</td>"));
+        assert!(out.contains("<p class=\"byline\">bob<span class=\"when\"> APPROVED:</span></p>"));
+        assert!(out.contains("<div class=\"body\"><p>Looks <strong>good</strong></p>"));
+        assert!(out.contains("<span class=\"del\">"));
+        assert!(out.contains("<span class=\"add\">"));
+        assert!(out.contains("<p>Use <code>x</code></p>"));
+    }
+
+    #[test]
+    fn comment_html_is_sanitised() {
+        let body = "Hi <script>alert(1)</script><img src=\"https://e.test/x.png\" alt=\"pic\"> \
+                    <a href=\"javascript:x()\" onclick=\"y()\">link</a>";
+        let out = render(&[thread(body)], &[]);
+        assert!(!out.contains("<script"));
+        assert!(!out.contains("<img"));
+        assert!(!out.contains("javascript:"));
+        assert!(!out.contains("onclick"));
+        assert!(out.contains(">pic</a>"));
+    }
+
+    #[test]
+    fn report_loads_no_external_assets() {
+        let out = render(&[thread("![x](https://e.test/x.png)")], &[]);
+        for needle in ["<img", "<link", "<script", " src="] {
+            assert!(!out.contains(needle), "found {needle}");
+        }
+    }
+}
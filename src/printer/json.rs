@@ -0,0 +1,363 @@
+//! JSON rendering of a pull request's review state.
+//!
+//! The document is intended for tools and agents that would otherwise scrape
+//! the Termimad output. Its shape is versioned through [`SCHEMA_VERSION`];
+//! additive fields keep the version, while renamed or removed fields bump it.
+//! [`NdjsonEvent`] carries the same data as a stream of one object per line.
+
+use serde::Serialize;
+
+use crate::ReviewThread;
+use crate::ref_parser::RepoInfo;
+use crate::reviews::PullRequestReview;
+
+/// Version of the JSON document schema emitted by `vk pr --format json`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Identity of the pull request described by a document.
+#[derive(Debug, Serialize)]
+pub struct PullRequestRef<'a> {
+    pub owner: &'a str,
+    pub repo: &'a str,
+    pub number: u64,
+}
+
+impl<'a> PullRequestRef<'a> {
+    /// Borrow the identity of pull request `number` in `repo`.
+    #[must_use]
+    pub fn new(repo: &'a RepoInfo, number: u64) -> Self {
+        Self {
+            owner: &repo.owner,
+            repo: &repo.name,
+            number,
+        }
+    }
+}
+
+/// Comment count for a single file, mirroring the text summary.
+#[derive(Debug, Serialize)]
+pub struct FileSummary<'a> {
+    pub path: &'a str,
+    pub comments: usize,
+}
+
+/// Complete review state of a pull request.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrDocument<'a> {
+    pub schema_version: u32,
+    pub pull_request: PullRequestRef<'a>,
+    pub summary: Vec<FileSummary<'a>>,
+    pub reviews: &'a [PullRequestReview],
+    pub threads: &'a [ReviewThread],
+}
+
+impl<'a> PrDocument<'a> {
+    /// Assemble a document from the pieces printed by the text renderer.
+    #[must_use]
+    pub fn new(
+        pull_request: PullRequestRef<'a>,
+        summary: &'a [(String, usize)],
+        reviews: &'a [PullRequestReview],
+        threads: &'a [ReviewThread],
+    ) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            pull_request,
+            summary: file_summaries(summary),
+            reviews,
+            threads,
+        }
+    }
+}
+
+/// Write `document` as pretty-printed JSON followed by a newline.
+///
+/// # Errors
+///
+/// Returns an error if serialisation fails or the writer reports an I/O
+/// error. I/O failures are surfaced as [`std::io::Error`] so callers can
+/// detect broken pipes.
+pub fn write_json_document<W: std::io::Write>(
+    mut out: W,
+    document: &PrDocument<'_>,
+) -> anyhow::Result<()> {
+    serde_json::to_writer_pretty(&mut out, document).map_err(std::io::Error::from)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// One line of `vk pr --format ndjson` output.
+///
+/// Events are tagged with a `type` field. `start` opens the stream with the
+/// schema version and pull request identity, then reviews are emitted,
+/// then each thread as soon as its comments are fetched. The per-file
+/// summary follows the threads because counts are only final once
+/// pagination finishes, and `end` closes the stream.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NdjsonEvent<'a> {
+    /// Schema version and identity of the pull request described.
+    #[serde(rename_all = "camelCase")]
+    Start {
+        schema_version: u32,
+        pull_request: PullRequestRef<'a>,
+    },
+    /// The latest review from one reviewer.
+    Review(&'a PullRequestReview),
+    /// A review thread with all of its comments.
+    Thread(&'a ReviewThread),
+    /// Per-file comment counts.
+    Summary { files: Vec<FileSummary<'a>> },
+    /// Marks the end of the stream.
+    End { reviews: usize, threads: usize },
+}
+
+impl<'a> NdjsonEvent<'a> {
+    /// Build the opening event for `pull_request`.
+    #[must_use]
+    pub const fn start(pull_request: PullRequestRef<'a>) -> Self {
+        Self::Start {
+            schema_version: SCHEMA_VERSION,
+            pull_request,
+        }
+    }
+
+    /// Build the summary event from a per-file comment summary.
+    #[must_use]
+    pub fn summary(summary: &'a [(String, usize)]) -> Self {
+        Self::Summary {
+            files: file_summaries(summary),
+        }
+    }
+}
+
+fn file_summaries(summary: &[(String, usize)]) -> Vec<FileSummary<'_>> {
+    summary
+        .iter()
+        .map(|(path, comments)| FileSummary {
+            path,
+            comments: *comments,
+        })
+        .collect()
+}
+
+/// Write `event` as a single line of compact JSON and flush the writer.
+///
+/// Flushing after every event lets consumers act on each thread while later
+/// threads are still being fetched.
+///
+/// # Errors
+///
+/// Returns an error if serialisation fails or the writer reports an I/O
+/// error. I/O failures are surfaced as [`std::io::Error`] so callers can
+/// detect broken pipes.
+pub fn write_ndjson_event<W: std::io::Write>(
+    mut out: W,
+    event: &NdjsonEvent<'_>,
+) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut out, event).map_err(std::io::Error::from)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review_threads::{ReactionContent, ReactionGroup, ReactorCount};
+    use crate::{CommentConnection, ReviewComment, User};
+    use serde_json::{Value, json};
+
+    fn render(document: &PrDocument<'_>) -> Value {
+        let mut buf = Vec::new();
+        write_json_document(&mut buf, document).expect("write json");
+        serde_json::from_slice(&buf).expect("parse json")
+    }
+
+    #[test]
+    fn document_includes_identity_summary_and_threads() {
+        let repo = RepoInfo {
+            owner: "leynos".into(),
+            name: "vk".into(),
+        };
+        let threads = [ReviewThread {
+            id: "t1".into(),
+            is_outdated: true,
+            comments: CommentConnection {
+                nodes: vec![ReviewComment {
+                    body: "Fix this".into(),
+                    diff_hunk: "@@ -1 +1 @@\n-old\n+new".into(),
+                    position: Some(1),
+                    path: "src/lib.rs".into(),
+                    url: "https://example.com#discussion_r1".into(),
+                    author: Some(User {
+                        login: "alice".into(),
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }];
+        let summary = vec![("src/lib.rs".to_string(), 1)];
+        let value = render(&PrDocument::new(
+            PullRequestRef::new(&repo, 42),
+            &summary,
+            &[],
+            &threads,
+        ));
+
+        assert_eq!(
+            value.pointer("/schemaVersion"),
+            Some(&json!(SCHEMA_VERSION))
+        );
+        assert_eq!(value.pointer("/pullRequest/owner"), Some(&json!("leynos")));
+        assert_eq!(value.pointer("/pullRequest/number"), Some(&json!(42)));
+        assert_eq!(value.pointer("/summary/0/path"), Some(&json!("src/lib.rs")));
+        assert_eq!(value.pointer("/summary/0/comments"), Some(&json!(1)));
+        let thread = value.pointer("/threads/0").expect("thread");
+        assert_eq!(thread.pointer("/isOutdated"), Some(&json!(true)));
+        assert_eq!(thread.pointer("/isResolved"), Some(&json!(false)));
+        let comment = thread.pointer("/comments/0").expect("comment");
+        assert_eq!(
+            comment.pointer("/diffHunk"),
+            Some(&json!("@@ -1 +1 @@\n-old\n+new"))
+        );
+        assert_eq!(comment.pointer("/position"), Some(&json!(1)));
+        assert_eq!(comment.pointer("/originalPosition"), Some(&Value::Null));
+        assert_eq!(comment.pointer("/author/login"), Some(&json!("alice")));
+    }
+
+    #[test]
+    fn reactions_are_flattened_to_counts() {
+        let group = |content, total_count| ReactionGroup {
+            content,
+            reactors: ReactorCount { total_count },
+        };
+        let threads = [ReviewThread {
+            comments: CommentConnection {
+                nodes: vec![
+                    ReviewComment {
+                        reactions: vec![
+                            group(ReactionContent::ThumbsUp, 2),
+                            group(ReactionContent::Heart, 0),
+                        ],
+                        ..Default::default()
+                    },
+                    ReviewComment {
+                        reactions: vec![group(ReactionContent::Eyes, 0)],
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        }];
+        let repo = RepoInfo {
+            owner: "o".into(),
+            name: "r".into(),
+        };
+        let value = render(&PrDocument::new(
+            PullRequestRef::new(&repo, 1),
+            &[],
+            &[],
+            &threads,
+        ));
+        let comments = value.pointer("/threads/0/comments").expect("comments");
+        assert_eq!(
+            comments.pointer("/0/reactions"),
+            Some(&json!([{"content": "THUMBS_UP", "count": 2}]))
+        );
+        assert_eq!(comments.pointer("/0/reactionGroups"), None);
+        assert_eq!(comments.pointer("/1/reactions"), None);
+    }
+
+    #[test]
+    fn document_serialises_reviews() {
+        let repo = RepoInfo {
+            owner: "o".into(),
+            name: "r".into(),
+        };
+        let reviews = [PullRequestReview {
+            body: "Looks good".into(),
+            submitted_at: None,
+            state: "APPROVED".into(),
+            author: None,
+        }];
+        let value = render(&PrDocument::new(
+            PullRequestRef::new(&repo, 1),
+            &[],
+            &reviews,
+            &[],
+        ));
+        assert_eq!(value.pointer("/reviews/0/state"), Some(&json!("APPROVED")));
+        assert_eq!(value.pointer("/reviews/0/submittedAt"), Some(&Value::Null));
+        assert_eq!(value.pointer("/threads"), Some(&json!([])));
+    }
+
+    fn render_event(event: &NdjsonEvent<'_>) -> String {
+        let mut buf = Vec::new();
+        write_ndjson_event(&mut buf, event).expect("write event");
+        String::from_utf8(buf).expect("utf8")
+    }
+
+    #[test]
+    fn ndjson_events_are_tagged_single_lines() {
+        let thread = ReviewThread {
+            id: "t1".into(),
+            comments: CommentConnection {
+                nodes: vec![ReviewComment {
+                    body: "line one\nline two".into(),
+                    path: "a.rs".into(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let line = render_event(&NdjsonEvent::Thread(&thread));
+        assert_eq!(
+            line.matches('\n').count(),
+            1,
+            "one event per line: {line:?}"
+        );
+        let value: Value = serde_json::from_str(&line).expect("parse event");
+        assert_eq!(value.pointer("/type"), Some(&json!("thread")));
+        assert_eq!(value.pointer("/id"), Some(&json!("t1")));
+        assert_eq!(value.pointer("/comments/0/path"), Some(&json!("a.rs")));
+    }
+
+    #[test]
+    fn ndjson_start_summary_and_end_events() {
+        let repo = RepoInfo {
+            owner: "o".into(),
+            name: "r".into(),
+        };
+        let value: Value = serde_json::from_str(&render_event(&NdjsonEvent::start(
+            PullRequestRef::new(&repo, 7),
+        )))
+        .expect("parse start");
+        assert_eq!(value.pointer("/type"), Some(&json!("start")));
+        assert_eq!(
+            value.pointer("/schemaVersion"),
+            Some(&json!(SCHEMA_VERSION))
+        );
+        assert_eq!(value.pointer("/pullRequest/number"), Some(&json!(7)));
+
+        let summary = vec![("a.rs".to_string(), 3)];
+        let value: Value = serde_json::from_str(&render_event(&NdjsonEvent::summary(&summary)))
+            .expect("parse summary");
+        assert_eq!(
+            value,
+            json!({"type": "summary", "files": [{"path": "a.rs", "comments": 3}]})
+        );
+
+        let value: Value = serde_json::from_str(&render_event(&NdjsonEvent::End {
+            reviews: 0,
+            threads: 2,
+        }))
+        .expect("parse end");
+        assert_eq!(value, json!({"type": "end", "reviews": 0, "threads": 2}));
+    }
+}
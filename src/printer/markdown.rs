@@ -0,0 +1,563 @@
+//! Plain GitHub-flavoured Markdown rendering for pipes and agents.
+//!
+//! Unlike the Termimad renderer this module never emits terminal escape
+//! sequences. Each file gets a heading, diff hunks are placed in fenced
+//! `diff` blocks, and permalinks become ordinary Markdown links, so the
+//! output reads well both as raw text and once rendered.
+
+use std::borrow::Cow;
+use std::io::Write;
+use std::sync::LazyLock;
+
+use super::{Formattable, RawComment, banner_tail, reactions_line, resolution_note};
+use crate::cli_args::{DiffContext, GroupBy, TimestampStyle};
+use crate::diff::comment_diff_excerpt;
+use crate::reviews::PullRequestReview;
+use crate::thread_order::ThreadGroup;
+use crate::worktree::local_note;
+use crate::{ReviewComment, ReviewThread};
+use regex::Regex;
+use vk::icons::Icons;
+
+/// Runs of backticks, which open and close inline code spans.
+static BACKTICKS_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new("`+").expect("valid regex"));
+
+/// Write the author line and Markdown body of a review or comment, putting
+/// the matches of `matches` in bold.
+fn write_markdown_item<W: Write, T: Formattable>(
+    mut out: W,
+    item: &T,
+    icons: Icons,
+    timestamps: TimestampStyle,
+    matches: Option<&Regex>,
+) -> anyhow::Result<()> {
+    writeln!(
+        out,
+        "**{}**{}",
+        item.author_login().unwrap_or("(unknown)"),
+        banner_tail(item, timestamps)
+    )?;
+    writeln!(out)?;
+    let body = item.body(icons);
+    let body = body.trim_end();
+    let body = matches.map_or(Cow::Borrowed(body), |re| emphasise_matches(body, re));
+    if !body.is_empty() {
+        writeln!(out, "{body}")?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Put the matches of `pattern` in `body` in bold.
+///
+/// Fenced code blocks and inline code spans are left alone, since emphasis
+/// would show there literally. Each line is searched on its own.
+fn emphasise_matches<'a>(body: &'a str, pattern: &Regex) -> Cow<'a, str> {
+    if !pattern.is_match(body) {
+        return Cow::Borrowed(body);
+    }
+    let mut out = String::with_capacity(body.len());
+    let mut fence: Option<(char, usize)> = None;
+    for line in body.split_inclusive('\n') {
+        match (fence, fence_marker(line)) {
+            (None, Some(open)) => {
+                fence = Some(open);
+                out.push_str(line);
+            }
+            (Some((c, len)), Some((close, close_len))) if close == c && close_len >= len => {
+                fence = None;
+                out.push_str(line);
+            }
+            (Some(_), _) => out.push_str(line),
+            (None, None) => emphasise_line(&mut out, line, pattern),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// The fence character and length when `line` opens or closes a fenced
+/// code block.
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start();
+    let c = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.chars().take_while(|&x| x == c).count();
+    (len >= 3).then_some((c, len))
+}
+
+/// Append `line` to `out`, emphasising matches outside inline code spans.
+///
+/// A span runs from a backtick run to the next run of the same length.
+fn emphasise_line(out: &mut String, line: &str, pattern: &Regex) {
+    let runs: Vec<_> = BACKTICKS_RE.find_iter(line).collect();
+    let mut last = 0;
+    let mut i = 0;
+    while let Some(open) = runs.get(i) {
+        let rest = runs.get(i + 1..).unwrap_or_default();
+        let Some(j) = rest.iter().position(|r| r.len() == open.len()) else {
+            i += 1;
+            continue;
+        };
+        let close_end = rest.get(j).map_or(line.len(), regex::Match::end);
+        emphasise_text(
+            out,
+            line.get(last..open.start()).unwrap_or_default(),
+            pattern,
+        );
+        out.push_str(line.get(open.start()..close_end).unwrap_or_default());
+        last = close_end;
+        i += j + 2;
+    }
+    emphasise_text(out, line.get(last..).unwrap_or_default(), pattern);
+}
+
+/// Append `text` to `out` with each match of `pattern` wrapped in `**`.
+///
+/// Whitespace at either end of a match stays outside the markers, which
+/// would otherwise not count as emphasis.
+fn emphasise_text(out: &mut String, text: &str, pattern: &Regex) {
+    let mut last = 0;
+    for found in pattern.find_iter(text) {
+        let core = found.as_str().trim();
+        if core.is_empty() {
+            continue;
+        }
+        let start = found.start() + (found.as_str().len() - found.as_str().trim_start().len());
+        out.push_str(text.get(last..start).unwrap_or_default());
+        out.push_str("**");
+        out.push_str(core);
+        out.push_str("**");
+        last = start + core.len();
+    }
+    out.push_str(text.get(last..).unwrap_or_default());
+}
+
+/// Choose a backtick fence longer than any backtick run in `content`.
+pub(super) fn fence_for(content: &str) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(std::cmp::max(3, longest + 1))
+}
+
+/// Write reviews as bold author lines followed by their Markdown bodies,
+/// marking collapsed `<details>` blocks with `icons` and showing submission
+/// times in `timestamps` style.
+///
+/// # Examples
+///
+/// ```ignore
+/// use vk::printer::markdown::write_markdown_reviews;
+/// use vk::reviews::PullRequestReview;
+/// let review = PullRequestReview { body: "Nice".into(), submitted_at: None, state: "APPROVED".into(), author: None };
+/// let mut buf = Vec::new();
+/// write_markdown_reviews(&mut buf, &[review], Icons::default(), TimestampStyle::default()).unwrap();
+/// ```
+pub fn write_markdown_reviews<W: Write>(
+    mut out: W,
+    reviews: &[PullRequestReview],
+    icons: Icons,
+    timestamps: TimestampStyle,
+) -> anyhow::Result<()> {
+    for review in reviews {
+        write_markdown_item(&mut out, review, icons, timestamps, None)?;
+    }
+    Ok(())
+}
+
+/// Writes review threads as Markdown, adding a heading whenever the file
+/// changes between consecutive threads.
+///
+/// Under an author group heading, file headings drop one level.
+#[derive(Debug, Default)]
+pub struct MarkdownThreadWriter {
+    current_path: Option<String>,
+    in_author_group: bool,
+    context: DiffContext,
+    raw_body: bool,
+    icons: Icons,
+    timestamps: TimestampStyle,
+    matches: Option<Regex>,
+}
+
+impl MarkdownThreadWriter {
+    /// Create a writer showing `context` lines around each commented line.
+    #[must_use]
+    pub fn new(context: DiffContext) -> Self {
+        Self {
+            current_path: None,
+            in_author_group: false,
+            context,
+            raw_body: false,
+            icons: Icons::default(),
+            timestamps: TimestampStyle::default(),
+            matches: None,
+        }
+    }
+
+    /// Show comment bodies exactly as posted instead of extracting bot
+    /// comments and collapsing `<details>` blocks.
+    #[must_use]
+    pub const fn with_raw_body(mut self, raw_body: bool) -> Self {
+        self.raw_body = raw_body;
+        self
+    }
+
+    /// Mark collapsed `<details>` blocks with the glyph from `icons`.
+    #[must_use]
+    pub const fn with_icons(mut self, icons: Icons) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// Glyphs this writer uses.
+    #[must_use]
+    pub const fn icons(&self) -> Icons {
+        self.icons
+    }
+
+    /// Show posting times in the given style.
+    #[must_use]
+    pub const fn with_timestamps(mut self, timestamps: TimestampStyle) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
+
+    /// How this writer shows posting times.
+    #[must_use]
+    pub const fn timestamps(&self) -> TimestampStyle {
+        self.timestamps
+    }
+
+    /// Put the matches of a `--grep` pattern in comment bodies in bold.
+    #[must_use]
+    pub fn with_matches(mut self, matches: Option<Regex>) -> Self {
+        self.matches = matches;
+        self
+    }
+
+    /// Write the heading for a group of threads.
+    ///
+    /// A file group's heading stands in for the file heading, so the first
+    /// thread does not repeat it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing to `out` fails.
+    pub fn write_group_heading<W: Write>(
+        &mut self,
+        mut out: W,
+        group: &ThreadGroup,
+    ) -> anyhow::Result<()> {
+        writeln!(out)?;
+        match group.by {
+            GroupBy::File => {
+                writeln!(out, "## `{}` ({})", group.key, group.count_label())?;
+                self.current_path = Some(group.key.clone());
+                self.in_author_group = false;
+            }
+            GroupBy::Author => {
+                writeln!(out, "## {} ({})", group.key, group.count_label())?;
+                self.current_path = None;
+                self.in_author_group = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write all comments in `thread`.
+    ///
+    /// The first comment carries the diff hunk; replies share it and so
+    /// omit it, mirroring the terminal layout.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use vk::printer::markdown::MarkdownThreadWriter;
+    /// use vk::ReviewThread;
+    /// let mut writer = MarkdownThreadWriter::default();
+    /// let mut buf = Vec::new();
+    /// writer.write_thread(&mut buf, &ReviewThread::default()).unwrap();
+    /// ```
+    pub fn write_thread<W: Write>(
+        &mut self,
+        mut out: W,
+        thread: &ReviewThread,
+    ) -> anyhow::Result<()> {
+        let mut iter = thread.comments.nodes.iter();
+        let Some(first) = iter.next() else {
+            return Ok(());
+        };
+        if self.current_path.as_deref() != Some(first.path.as_str()) {
+            let level = if self.in_author_group { "###" } else { "##" };
+            writeln!(out)?;
+            writeln!(out, "{level} `{}`", first.path)?;
+            self.current_path = Some(first.path.clone());
+        }
+        self.write_comment(&mut out, first, Some(thread))?;
+        for comment in iter {
+            self.write_comment(&mut out, comment, None)?;
+        }
+        Ok(())
+    }
+
+    /// Write one thread comment: permalink, the resolution note and what is
+    /// known about the working tree, an optional diff block, the body, then
+    /// any reactions.
+    ///
+    /// The first comment of a thread receives the `thread` and carries the
+    /// diff block.
+    ///
+    /// Each comment ends with a `---` thematic break preceded by a blank line
+    /// so the body's last paragraph is not turned into a setext heading.
+    fn write_comment<W: Write>(
+        &self,
+        mut out: W,
+        comment: &ReviewComment,
+        thread: Option<&ReviewThread>,
+    ) -> anyhow::Result<()> {
+        writeln!(out)?;
+        writeln!(out, "[Permalink]({})", comment.url)?;
+        writeln!(out)?;
+        if let Some(note) = thread.and_then(resolution_note) {
+            writeln!(out, "_{note}_")?;
+            writeln!(out)?;
+        }
+        if let Some(note) = local_note(comment, thread.and_then(|t| t.local_status)) {
+            writeln!(out, "_{note}_")?;
+            writeln!(out)?;
+        }
+        if thread.is_some() {
+            let (info, excerpt) = match &comment.head_context {
+                Some(head) => ("text", head.format()?),
+                None => ("diff", comment_diff_excerpt(comment, self.context)?),
+            };
+            if !excerpt.is_empty() {
+                let fence = fence_for(&excerpt);
+                writeln!(out, "{fence}{info}")?;
+                write!(out, "{excerpt}")?;
+                writeln!(out, "{fence}")?;
+                writeln!(out)?;
+            }
+        }
+        let matches = self.matches.as_ref();
+        if self.raw_body {
+            write_markdown_item(
+                &mut out,
+                &RawComment(comment),
+                self.icons,
+                self.timestamps,
+                matches,
+            )?;
+        } else {
+            write_markdown_item(&mut out, comment, self.icons, self.timestamps, matches)?;
+        }
+        if let Some(line) = reactions_line(&comment.reactions, self.icons) {
+            writeln!(out, "{line}")?;
+            writeln!(out)?;
+        }
+        writeln!(out, "---")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review_threads::{ReactionContent, ReactionGroup, ReactorCount};
+    use crate::worktree::LocalStatus;
+    use crate::{CommentConnection, User};
+
+    fn comment(path: &str, body: &str, url: &str) -> ReviewComment {
+        ReviewComment {
+            body: body.into(),
+            diff_hunk: "@@ -1 +1 @@\n-old\n+new\n".into(),
+            path: path.into(),
+            url: url.into(),
+            author: Some(User {
+                login: "alice".into(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn thread_with(comments: Vec<ReviewComment>) -> ReviewThread {
+        ReviewThread {
+            comments: CommentConnection {
+                nodes: comments,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn render(threads: &[ReviewThread]) -> String {
+        let mut writer = MarkdownThreadWriter::default();
+        let mut buf = Vec::new();
+        for thread in threads {
+            writer.write_thread(&mut buf, thread).expect("write thread");
+        }
+        String::from_utf8(buf).expect("utf8")
+    }
+
+    #[test]
+    fn thread_renders_plain_markdown() {
+        let out = render(&[thread_with(vec![
+            comment("src/lib.rs", "Please **fix**", "http://u1"),
+            comment("src/lib.rs", "Done", "http://u2"),
+        ])]);
+        assert_eq!(
+            out,
+            concat!(
+                "\n## `src/lib.rs`\n",
+                "\n[Permalink](http://u1)\n\n",
+                "```diff\n@@ -1,1 +1,1 @@\n-old\n+new\n```\n\n",
+                "**alice** wrote:\n\nPlease **fix**\n\n---\n",
+                "\n[Permalink](http://u2)\n\n",
+                "**alice** wrote:\n\nDone\n\n---\n",
+            )
+        );
+        assert!(!out.contains('\u{1b}'));
+    }
+
+    #[test]
+    fn heading_repeats_only_when_file_changes() {
+        let out = render(&[
+            thread_with(vec![comment("a.rs", "one", "http://u1")]),
+            thread_with(vec![comment("a.rs", "two", "http://u2")]),
+            thread_with(vec![comment("b.rs", "three", "http://u3")]),
+        ]);
+        assert_eq!(out.matches("## `a.rs`").count(), 1);
+        assert_eq!(out.matches("## `b.rs`").count(), 1);
+    }
+
+    #[test]
+    fn author_groups_nest_file_headings() {
+        let mut writer = MarkdownThreadWriter::default();
+        let group = ThreadGroup {
+            by: GroupBy::Author,
+            key: "alice".into(),
+            threads: vec![thread_with(vec![comment("a.rs", "one", "http://u1")])],
+        };
+        let mut buf = Vec::new();
+        writer
+            .write_group_heading(&mut buf, &group)
+            .expect("write heading");
+        for thread in &group.threads {
+            writer.write_thread(&mut buf, thread).expect("write thread");
+        }
+        let out = String::from_utf8(buf).expect("utf8");
+        assert!(
+            out.starts_with("\n## alice (1 thread)\n\n### `a.rs`\n"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn fence_outgrows_backticks_in_diff() {
+        let mut c = comment("README.md", "", "http://u1");
+        c.diff_hunk = "@@ -1 +1 @@\n-```rust\n+````rust\n".into();
+        let out = render(&[thread_with(vec![c])]);
+        assert!(out.contains("\n`````diff\n"));
+        assert!(out.contains("\n`````\n"));
+    }
+
+    #[test]
+    fn head_context_replaces_diff_block() {
+        let mut c = comment("src/lib.rs", "Look here", "http://u1");
+        c.head_context = Some(crate::head_context::FileExcerpt {
+            start_line: 9,
+            target_line: 10,
+            lines: vec!["fn a() {}".into(), "fn b() {}".into()],
+        });
+        let out = render(&[thread_with(vec![c])]);
+        assert!(out.contains("```text\n    9| fn a() {}\n   10|>fn b() {}\n```\n"));
+        assert!(!out.contains("```diff"));
+    }
+
+    #[test]
+    fn local_line_precedes_diff_block() {
+        let mut c = comment("src/lib.rs", "Look here", "http://u1");
+        c.local_line = Some(crate::worktree::LocalLine::Present { line: 12 });
+        let out = render(&[thread_with(vec![c])]);
+        assert!(out.contains("[Permalink](http://u1)\n\n_local src/lib.rs:12_\n\n```diff\n"));
+    }
+
+    #[test]
+    fn local_status_joins_local_line() {
+        let mut c = comment("src/lib.rs", "Look here", "http://u1");
+        c.local_line = Some(crate::worktree::LocalLine::Deleted);
+        let mut t = thread_with(vec![c]);
+        t.local_status = Some(LocalStatus::LinesRemoved);
+        let out = render(&[t]);
+        assert!(out.contains("\n_lines deleted locally; lines removed_\n"));
+    }
+
+    #[test]
+    fn resolution_and_reactions_frame_the_comment() {
+        let mut c = comment("src/lib.rs", "Looks off", "http://u1");
+        c.reactions = vec![
+            ReactionGroup {
+                content: ReactionContent::ThumbsUp,
+                reactors: ReactorCount { total_count: 2 },
+            },
+            ReactionGroup {
+                content: ReactionContent::Heart,
+                reactors: ReactorCount { total_count: 0 },
+            },
+        ];
+        let mut t = thread_with(vec![c]);
+        t.is_resolved = true;
+        t.resolved_by = Some(User {
+            login: "bob".into(),
+        });
+        let out = render(&[t]);
+        assert!(out.contains("[Permalink](http://u1)\n\n_Resolved by bob_\n\n```diff\n"));
+        assert!(out.contains("Looks off\n\n\u{1f44d} 2\n\n---\n"), "{out}");
+        assert!(!out.contains("\u{2764}"));
+    }
+
+    #[test]
+    fn reviews_render_without_escapes() {
+        let review = PullRequestReview {
+            body: "<details><summary>note</summary>hidden</details>".into(),
+            submitted_at: None,
+            state: "APPROVED".into(),
+            author: Some(User {
+                login: "bob".into(),
+            }),
+        };
+        let mut buf = Vec::new();
+        write_markdown_reviews(
+            &mut buf,
+            &[review],
+            Icons::default(),
+            TimestampStyle::default(),
+        )
+        .expect("write reviews");
+        let out = String::from_utf8(buf).expect("utf8");
+        assert!(out.starts_with("**bob** APPROVED:\n\n"));
+        assert!(out.contains("\u{25B6} note"));
+        assert!(!out.contains("hidden"));
+        assert!(!out.contains('\u{1b}'));
+    }
+
+    #[test]
+    fn search_matches_are_bold_outside_code() {
+        let body = "Avoid unwrap here\n\n```rust\nx.unwrap()\n```\n\nSee `unwrap` and unwrap.";
+        let mut writer = MarkdownThreadWriter::default()
+            .with_matches(Some(Regex::new("unwrap ?").expect("valid regex")));
+        let mut buf = Vec::new();
+        writer
+            .write_thread(
+                &mut buf,
+                &thread_with(vec![comment("src/lib.rs", body, "http://u1")]),
+            )
+            .expect("write thread");
+        let out = String::from_utf8(buf).expect("utf8");
+        assert!(
+            out.contains(concat!(
+                "Avoid **unwrap** here\n\n```rust\nx.unwrap()\n```\n\n",
+                "See `unwrap` and **unwrap**.\n",
+            )),
+            "{out}"
+        );
+    }
+}
@@ -2,25 +2,80 @@
 //!
 //! These functions format comments with syntax highlighting using
 //! `termimad`. They are separated from the rest of the application so
-//! behaviour can be unit tested without capturing stdout.
+//! behaviour can be unit tested without capturing stdout. Machine-readable
+//! formats live in submodules.
+use std::borrow::Cow;
+
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+
 use termimad::MadSkin;
 
+pub mod bots;
+pub mod html_report;
+pub mod json;
+pub mod markdown;
+pub mod quickfix;
+pub mod sarif;
+pub mod template;
+
+use crate::cli_args::{DiffContext, GroupBy, TimestampStyle};
 use crate::diff::format_comment_diff;
-use crate::html::collapse_details;
+use crate::highlight::highlight_diff;
+use crate::hyperlink::{LinkTargets, osc8};
+use crate::review_threads::ReactionGroup;
 use crate::reviews::PullRequestReview;
+use crate::suggestion::render_suggestions;
+use crate::theme::{Palette, fg_escape};
+use crate::thread_order::ThreadGroup;
+use crate::timestamps::{describe_time, edited_after_posting};
+use crate::worktree::local_note;
 use crate::{ReviewComment, ReviewThread};
-use vk::icons::{ICON_COMMENT, ICON_FILE, ICON_PERMALINK, ICON_REVIEW};
+use bots::extract_bot_comment;
+use markdown::{MarkdownThreadWriter, write_markdown_reviews};
+use vk::html::collapse_details_with;
+use vk::icons::Icons;
+
+/// Options controlling the terminal renderer.
+#[derive(Debug, Default, Clone)]
+pub struct RenderOptions {
+    /// Highlight diff hunks by language on top of add/remove colouring.
+    pub highlight: bool,
+    /// Diff lines shown around each commented line.
+    pub context: DiffContext,
+    /// Show comment bodies as posted, skipping bot extraction and
+    /// `<details>` collapsing.
+    pub raw_body: bool,
+    /// Glyphs prefixing permalinks, paths, banners and collapsed blocks.
+    pub icons: Icons,
+    /// Colours for diff hunks and author names.
+    pub palette: Palette,
+    /// Targets for OSC 8 hyperlinks, or `None` to print plain text.
+    pub links: Option<LinkTargets>,
+    /// How author banners show posting times.
+    pub timestamps: TimestampStyle,
+    /// Search pattern whose matches in threads are shown in reverse video.
+    pub matches: Option<Regex>,
+}
 
 fn write_author_line<W: std::io::Write>(
     out: &mut W,
     icon: &str,
     login: Option<&str>,
     suffix: &str,
+    options: &RenderOptions,
 ) -> std::io::Result<()> {
+    let name = match (login, &options.links) {
+        (Some(login), Some(_)) => osc8(&LinkTargets::profile(login), login),
+        (Some(login), None) => login.to_owned(),
+        (None, _) => "(unknown)".to_owned(),
+    };
     writeln!(
         out,
-        "{icon}  \x1b[1m{}\x1b[0m{suffix}",
-        login.unwrap_or("(unknown)")
+        "{icon}  \x1b[1m{}{name}\x1b[0m{suffix}",
+        fg_escape(options.palette.author),
     )
 }
 
@@ -28,12 +83,33 @@ fn write_author_line<W: std::io::Write>(
 trait Formattable {
     /// Login for the author, if available.
     fn author_login(&self) -> Option<&str>;
-    /// Text content to render below the banner.
-    fn body(&self) -> &str;
+    /// Text content to render below the banner, prepared for display with
+    /// `icons` marking collapsed blocks.
+    fn body(&self, icons: Icons) -> Cow<'_, str>;
     /// Icon prefixing the banner.
-    fn icon(&self) -> &'static str;
-    /// Suffix appended after the author.
+    fn icon(&self, icons: Icons) -> &'static str;
+    /// Text following the author, such as ` wrote`; [`banner_tail`] adds
+    /// the posting time and the closing colon.
     fn suffix(&self) -> String;
+    /// When the item was posted, if known.
+    fn posted_at(&self) -> Option<DateTime<Utc>>;
+    /// Whether the item was edited after it was posted.
+    fn edited(&self) -> bool;
+}
+
+/// Finish an author banner: the item's suffix, when it was posted, an
+/// `(edited)` marker for edited items, and a colon.
+fn banner_tail<T: Formattable>(item: &T, style: TimestampStyle) -> String {
+    let mut tail = item.suffix();
+    if let Some(posted) = item.posted_at() {
+        tail.push(' ');
+        tail.push_str(&describe_time(posted, Utc::now(), style));
+    }
+    if item.edited() {
+        tail.push_str(" (edited)");
+    }
+    tail.push(':');
+    tail
 }
 
 impl Formattable for ReviewComment {
@@ -41,16 +117,27 @@ impl Formattable for ReviewComment {
         self.author.as_ref().map(|u| u.login.as_str())
     }
 
-    fn body(&self) -> &str {
-        &self.body
+    fn body(&self, icons: Icons) -> Cow<'_, str> {
+        Cow::Owned(
+            extract_bot_comment(self, icons)
+                .unwrap_or_else(|| tidy_body(&render_suggestions(self), icons)),
+        )
     }
 
-    fn icon(&self) -> &'static str {
-        ICON_COMMENT
+    fn icon(&self, icons: Icons) -> &'static str {
+        icons.comment
     }
 
     fn suffix(&self) -> String {
-        " wrote:".to_string()
+        " wrote".to_string()
+    }
+
+    fn posted_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at
+    }
+
+    fn edited(&self) -> bool {
+        edited_after_posting(self.created_at, self.last_edited_at)
     }
 }
 
@@ -59,17 +146,59 @@ impl Formattable for PullRequestReview {
         self.author.as_ref().map(|u| u.login.as_str())
     }
 
-    fn body(&self) -> &str {
-        &self.body
+    fn body(&self, icons: Icons) -> Cow<'_, str> {
+        Cow::Owned(tidy_body(&self.body, icons))
+    }
+
+    fn icon(&self, icons: Icons) -> &'static str {
+        icons.review
+    }
+
+    fn suffix(&self) -> String {
+        format!(" {}", self.state)
+    }
+
+    fn posted_at(&self) -> Option<DateTime<Utc>> {
+        self.submitted_at
     }
 
-    fn icon(&self) -> &'static str {
-        ICON_REVIEW
+    fn edited(&self) -> bool {
+        false
+    }
+}
+
+/// A review comment whose body is shown exactly as posted.
+struct RawComment<'a>(&'a ReviewComment);
+
+impl Formattable for RawComment<'_> {
+    fn author_login(&self) -> Option<&str> {
+        self.0.author_login()
+    }
+
+    fn body(&self, _icons: Icons) -> Cow<'_, str> {
+        Cow::Borrowed(&self.0.body)
+    }
+
+    fn icon(&self, icons: Icons) -> &'static str {
+        self.0.icon(icons)
     }
 
     fn suffix(&self) -> String {
-        format!(" {}:", self.state)
+        self.0.suffix()
+    }
+
+    fn posted_at(&self) -> Option<DateTime<Utc>> {
+        self.0.posted_at()
     }
+
+    fn edited(&self) -> bool {
+        self.0.edited()
+    }
+}
+
+/// Collapse `<details>` blocks and runs of blank lines in `body`.
+fn tidy_body(body: &str, icons: Icons) -> String {
+    collapse_excessive_newlines(collapse_details_with(body, icons.collapsed))
 }
 
 /// Write a [`Formattable`] item with a banner and rendered markdown body.
@@ -82,7 +211,7 @@ impl Formattable for PullRequestReview {
 /// use termimad::MadSkin;
 /// let comment = ReviewComment { body: "hi".into(), ..Default::default() };
 /// let mut buf = Vec::new();
-/// write_formattable(&mut buf, &MadSkin::default(), &comment).unwrap();
+/// write_formattable(&mut buf, &MadSkin::default(), &comment, &RenderOptions::default()).unwrap();
 /// ```
 /// Collapse sequences of more than two newlines into at most two newlines.
 fn collapse_excessive_newlines(input: String) -> String {
@@ -110,12 +239,18 @@ fn write_formattable<W: std::io::Write, T: Formattable>(
     mut out: W,
     skin: &MadSkin,
     item: &T,
+    options: &RenderOptions,
 ) -> anyhow::Result<()> {
-    let suffix = item.suffix();
-    write_author_line(&mut out, item.icon(), item.author_login(), &suffix)?;
-    let collapsed = collapse_details(item.body());
-    let collapsed = collapse_excessive_newlines(collapsed);
-    let formatted = skin.text(&collapsed, None);
+    let suffix = banner_tail(item, options.timestamps);
+    write_author_line(
+        &mut out,
+        item.icon(options.icons),
+        item.author_login(),
+        &suffix,
+        options,
+    )?;
+    let body = item.body(options.icons);
+    let formatted = skin.text(&body, None);
     std::io::Write::write_fmt(&mut out, format_args!("{formatted}"))
         .map_err(anyhow::Error::from)?;
     writeln!(out)?;
@@ -125,7 +260,7 @@ fn write_formattable<W: std::io::Write, T: Formattable>(
 /// Format the body of a single review comment.
 ///
 /// The author's login appears in bold followed by the rendered markdown
-/// from the comment body.
+/// from the comment body, using the glyphs and colours in `options`.
 ///
 /// # Examples
 ///
@@ -136,41 +271,104 @@ fn write_formattable<W: std::io::Write, T: Formattable>(
 /// let skin = MadSkin::default();
 /// let comment = ReviewComment { body: "hello".into(), ..Default::default() };
 /// let mut buf = Vec::new();
-/// write_comment_body(&mut buf, &skin, &comment).unwrap();
+/// write_comment_body(&mut buf, &skin, &comment, &RenderOptions::default()).unwrap();
 /// ```
 pub fn write_comment_body<W: std::io::Write>(
     out: W,
     skin: &MadSkin,
     comment: &ReviewComment,
+    options: &RenderOptions,
 ) -> anyhow::Result<()> {
-    write_formattable(out, skin, comment)
+    write_formattable(out, skin, comment, options)
+}
+
+/// Describe who resolved `thread`, or `None` when it is unresolved.
+fn resolution_note(thread: &ReviewThread) -> Option<String> {
+    if !thread.is_resolved {
+        return None;
+    }
+    Some(thread.resolved_by.as_ref().map_or_else(
+        || "Resolved".to_owned(),
+        |user| format!("Resolved by {}", user.login),
+    ))
+}
+
+/// Summarise a comment's reactions on one line, such as `👍 3  👎 1`.
+///
+/// Returns `None` when nobody has reacted.
+fn reactions_line(reactions: &[ReactionGroup], icons: Icons) -> Option<String> {
+    let parts: Vec<String> = reactions
+        .iter()
+        .filter(|r| r.count() > 0)
+        .map(|r| format!("{} {}", r.content.glyph(icons.is_ascii()), r.count()))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("  "))
 }
 
 /// Write one comment of a review thread using the structured layout.
 ///
-/// The layout is, in order: a leading blank line, the globe-prefixed URL, a
-/// blank line, the document-prefixed file path and the formatted diff hunk
-/// (only when `include_diff` is true), a blank line, the author banner and
-/// rendered body, and finally a closing thematic break. The leading blank
-/// line pairs with the previous comment's closing `---` to provide the
-/// required spacing after the opening thematic break; the body's trailing
-/// newline collapses into a single blank line before the closing break.
+/// The layout is, in order: a leading blank line, the permalink-prefixed
+/// URL, the thread's resolution note when it is resolved, a blank line, the
+/// file-prefixed path (followed by the comment's working-tree location and
+/// the thread's local status, when known) and the formatted diff hunk, or
+/// the head-commit excerpt when one was fetched (only for the first comment,
+/// which receives its `thread`), a blank line, the author banner and
+/// rendered body, a line of reactions when there are any, and finally a
+/// closing thematic break. The leading blank line pairs with the previous
+/// comment's closing `---` to provide the required spacing after the
+/// opening thematic break; the body's trailing newline collapses into a
+/// single blank line before the closing break.
 fn write_thread_comment<W: std::io::Write>(
     mut out: W,
     skin: &MadSkin,
     comment: &ReviewComment,
-    include_diff: bool,
+    thread: Option<&ReviewThread>,
+    options: &RenderOptions,
 ) -> anyhow::Result<()> {
     writeln!(out)?;
-    writeln!(out, "{ICON_PERMALINK} {}", comment.url)?;
+    let icons = options.icons;
+    let links = options.links.as_ref();
+    let permalink = match links {
+        Some(_) => osc8(&comment.url, &comment.url),
+        None => comment.url.clone(),
+    };
+    writeln!(out, "{} {permalink}", icons.permalink)?;
+    if let Some(note) = thread.and_then(resolution_note) {
+        writeln!(out, "{} {note}", icons.resolved)?;
+    }
     writeln!(out)?;
-    if include_diff {
-        writeln!(out, "{ICON_FILE} {}:", comment.path)?;
-        let diff = format_comment_diff(comment)?;
-        write!(out, "{diff}")?;
+    if let Some(thread) = thread {
+        let path = links
+            .and_then(|l| l.file(comment))
+            .map_or_else(|| comment.path.clone(), |url| osc8(&url, &comment.path));
+        match local_note(comment, thread.local_status) {
+            Some(note) => writeln!(out, "{} {path}: ({note})", icons.file)?,
+            None => writeln!(out, "{} {path}:", icons.file)?,
+        }
+        let diff = match &comment.head_context {
+            Some(excerpt) => excerpt.format()?,
+            None => format_comment_diff(comment, options.context)?,
+        };
+        if options.highlight {
+            write!(
+                out,
+                "{}",
+                highlight_diff(&comment.path, &diff, options.palette)
+            )?;
+        } else {
+            write!(out, "{diff}")?;
+        }
+        writeln!(out)?;
+    }
+    if options.raw_body {
+        write_formattable(&mut out, skin, &RawComment(comment), options)?;
+    } else {
+        write_comment_body(&mut out, skin, comment, options)?;
+    }
+    if let Some(line) = reactions_line(&comment.reactions, icons) {
+        writeln!(out, "{line}")?;
         writeln!(out)?;
     }
-    write_comment_body(&mut out, skin, comment)?;
     writeln!(out, "---")?;
     Ok(())
 }
@@ -180,12 +378,13 @@ fn write_thread_comment<W: std::io::Write>(
 /// The first comment includes the file path and diff hunk. Subsequent
 /// comments share the same hunk and so omit both. Each comment is framed
 /// by a closing `---` thematic break; the break also serves as the opening
-/// break for the next comment in the thread.
+/// break for the next comment in the thread. `options` controls terminal
+/// features such as diff highlighting.
 ///
 /// # Examples
 ///
 /// ```ignore
-/// use vk::printer::write_thread;
+/// use vk::printer::{RenderOptions, write_thread};
 /// use vk::{ReviewComment, ReviewThread, CommentConnection};
 /// use termimad::MadSkin;
 /// let diff = "@@ -1 +1 @@\n-old\n+new\n";
@@ -193,24 +392,79 @@ fn write_thread_comment<W: std::io::Write>(
 /// let c2 = ReviewComment { diff_hunk: diff.into(), ..Default::default() };
 /// let thread = ReviewThread { comments: CommentConnection { nodes: vec![c1,c2], ..Default::default() }, ..Default::default() };
 /// let mut buf = Vec::new();
-/// write_thread(&mut buf, &MadSkin::default(), &thread).unwrap();
+/// write_thread(&mut buf, &MadSkin::default(), &thread, &RenderOptions::default()).unwrap();
 /// ```
 pub fn write_thread<W: std::io::Write>(
     mut out: W,
     skin: &MadSkin,
     thread: &ReviewThread,
+    options: &RenderOptions,
+) -> anyhow::Result<()> {
+    if let Some(pattern) = &options.matches {
+        let mut buf = Vec::new();
+        write_thread_comments(&mut buf, skin, thread, options)?;
+        let text = String::from_utf8_lossy(&buf);
+        out.write_all(highlight_matches(&text, pattern).as_bytes())?;
+        return Ok(());
+    }
+    write_thread_comments(out, skin, thread, options)
+}
+
+fn write_thread_comments<W: std::io::Write>(
+    mut out: W,
+    skin: &MadSkin,
+    thread: &ReviewThread,
+    options: &RenderOptions,
 ) -> anyhow::Result<()> {
     let mut iter = thread.comments.nodes.iter();
     let Some(first) = iter.next() else {
         return Ok(());
     };
-    write_thread_comment(&mut out, skin, first, true)?;
+    write_thread_comment(&mut out, skin, first, Some(thread), options)?;
     for c in iter {
-        write_thread_comment(&mut out, skin, c, false)?;
+        write_thread_comment(&mut out, skin, c, None, options)?;
     }
     Ok(())
 }
 
+/// Escape sequences in rendered output: CSI sequences such as colours, OSC
+/// sequences such as hyperlinks, and two-byte escapes.
+static ESCAPE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\x1b(?:\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(?:\x07|\x1b\\)|.)").expect("valid regex")
+});
+
+/// Show the matches of `pattern` in `text` in reverse video.
+///
+/// Only the visible text between escape sequences is searched, so styling
+/// and hyperlink targets are left intact. A match interrupted by styling,
+/// such as one spanning a bold word, is not highlighted.
+fn highlight_matches<'a>(text: &'a str, pattern: &Regex) -> Cow<'a, str> {
+    if !pattern.is_match(text) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut visible_start = 0;
+    for (start, end) in ESCAPE_RE
+        .find_iter(text)
+        .map(|m| (m.start(), m.end()))
+        .chain(std::iter::once((text.len(), text.len())))
+    {
+        let visible = text.get(visible_start..start).unwrap_or_default();
+        let mut last = 0;
+        for found in pattern.find_iter(visible).filter(|m| !m.is_empty()) {
+            out.push_str(visible.get(last..found.start()).unwrap_or_default());
+            out.push_str("\x1b[7m");
+            out.push_str(found.as_str());
+            out.push_str("\x1b[27m");
+            last = found.end();
+        }
+        out.push_str(visible.get(last..).unwrap_or_default());
+        out.push_str(text.get(start..end).unwrap_or_default());
+        visible_start = end;
+    }
+    Cow::Owned(out)
+}
+
 /// Print reviews to the provided writer using the given skin.
 ///
 /// Each review is printed with the reviewer's login followed by the
@@ -225,15 +479,16 @@ pub fn write_thread<W: std::io::Write>(
 /// use termimad::MadSkin;
 /// let review = PullRequestReview { body: "Looks good".into(), submitted_at: Utc::now(), state: "APPROVED".into(), author: None };
 /// let mut buf = Vec::new();
-/// print_reviews(&mut buf, &MadSkin::default(), &[review]).unwrap();
+/// print_reviews(&mut buf, &MadSkin::default(), &[review], &RenderOptions::default()).unwrap();
 /// ```
 pub fn print_reviews<W: std::io::Write>(
     mut out: W,
     skin: &MadSkin,
     reviews: &[PullRequestReview],
+    options: &RenderOptions,
 ) -> anyhow::Result<()> {
     for r in reviews {
-        write_review(&mut out, skin, r)?;
+        write_review(&mut out, skin, r, options)?;
     }
     Ok(())
 }
@@ -249,14 +504,98 @@ pub fn print_reviews<W: std::io::Write>(
 /// use termimad::MadSkin;
 /// let review = PullRequestReview { body: "Nice".into(), submitted_at: Utc::now(), state: "APPROVED".into(), author: None };
 /// let mut buf = Vec::new();
-/// write_review(&mut buf, &MadSkin::default(), &review).unwrap();
+/// write_review(&mut buf, &MadSkin::default(), &review, &RenderOptions::default()).unwrap();
 /// ```
 pub fn write_review<W: std::io::Write>(
     out: W,
     skin: &MadSkin,
     review: &PullRequestReview,
+    options: &RenderOptions,
 ) -> anyhow::Result<()> {
-    write_formattable(out, skin, review)
+    write_formattable(out, skin, review, options)
+}
+
+/// Write the heading introducing a group of threads.
+///
+/// File paths are shown as inline code; both kinds of heading end with the
+/// number of threads in the group.
+fn write_group_heading<W: std::io::Write>(
+    mut out: W,
+    skin: &MadSkin,
+    group: &ThreadGroup,
+) -> anyhow::Result<()> {
+    let title = match group.by {
+        GroupBy::File => format!("`{}`", group.key),
+        GroupBy::Author => group.key.clone(),
+    };
+    writeln!(out)?;
+    let heading = format!("## {title} ({})", group.count_label());
+    write!(out, "{}", skin.text(&heading, None))?;
+    Ok(())
+}
+
+/// Renderer for the human-readable, banner-framed output formats.
+#[derive(Debug)]
+pub enum Renderer {
+    /// Markdown styled for the terminal with Termimad.
+    Terminal {
+        skin: Box<MadSkin>,
+        options: RenderOptions,
+    },
+    /// Plain GitHub-flavoured Markdown without escape sequences.
+    Markdown(MarkdownThreadWriter),
+}
+
+impl Renderer {
+    /// Write reviews using this renderer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing to `out` fails.
+    pub fn write_reviews<W: std::io::Write>(
+        &self,
+        out: W,
+        reviews: &[PullRequestReview],
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Terminal { skin, options } => print_reviews(out, skin, reviews, options),
+            Self::Markdown(writer) => {
+                write_markdown_reviews(out, reviews, writer.icons(), writer.timestamps())
+            }
+        }
+    }
+
+    /// Write the heading for a group of threads using this renderer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing to `out` fails.
+    pub fn write_group_heading<W: std::io::Write>(
+        &mut self,
+        out: W,
+        group: &ThreadGroup,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Terminal { skin, .. } => write_group_heading(out, skin, group),
+            Self::Markdown(writer) => writer.write_group_heading(out, group),
+        }
+    }
+
+    /// Write a review thread using this renderer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when writing to `out` fails.
+    pub fn write_thread<W: std::io::Write>(
+        &mut self,
+        out: W,
+        thread: &ReviewThread,
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Terminal { skin, options } => write_thread(out, skin, thread, options),
+            Self::Markdown(writer) => writer.write_thread(out, thread),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,151 @@
+//! Compiler-style `path:line:col: message` output for editor quickfix lists.
+//!
+//! Each review thread is printed as one line that Vim's default
+//! `errorformat` and Emacs' `compilation-mode` recognise, so reviewers can
+//! step through unresolved comments with `:cexpr` or `next-error`.
+
+use std::io::Write;
+
+use crate::ReviewThread;
+use crate::diff::comment_line;
+use crate::worktree::{LocalLine, LocalStatus};
+
+/// Line used when no line number can be derived from the diff hunk.
+const FALLBACK_LINE: u32 = 1;
+
+/// Return the first non-blank line of `body`, trimmed.
+fn first_line(body: &str) -> &str {
+    body.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("(no comment)")
+}
+
+/// Write one quickfix entry per thread.
+///
+/// The entry uses the first comment's path, the line resolved by
+/// [`comment_line`] (or line 1 when none is available), column 1, the
+/// author's login, and the first non-blank line of the comment body. A line
+/// mapped into the working tree replaces the pull request line; comments
+/// whose lines were deleted locally keep it and end with `[deleted locally]`.
+/// Threads changed in the working tree end with their local status, such as
+/// `[modified locally]`.
+/// Threads without comments are skipped.
+///
+/// # Errors
+///
+/// Returns an error when writing to `out` fails.
+///
+/// # Examples
+///
+/// ```ignore
+/// use vk::printer::quickfix::write_quickfix;
+/// use vk::ReviewThread;
+/// let mut buf = Vec::new();
+/// write_quickfix(&mut buf, &[ReviewThread::default()]).unwrap();
+/// assert!(buf.is_empty());
+/// ```
+pub fn write_quickfix<W: Write>(mut out: W, threads: &[ReviewThread]) -> anyhow::Result<()> {
+    for thread in threads {
+        let Some(first) = thread.comments.nodes.first() else {
+            continue;
+        };
+        let pr_line = comment_line(first).unwrap_or(FALLBACK_LINE);
+        let (line, note) = match first.local_line {
+            Some(LocalLine::Present { line }) => (line, ""),
+            Some(LocalLine::Edited { line }) => (line, " [edited locally]"),
+            Some(LocalLine::Deleted) => (pr_line, " [deleted locally]"),
+            None => (pr_line, ""),
+        };
+        let status = thread
+            .local_status
+            .filter(|s| *s != LocalStatus::Untouched)
+            .map(|s| format!(" [{}]", s.label()))
+            .unwrap_or_default();
+        let author = first
+            .author
+            .as_ref()
+            .map_or("(unknown)", |u| u.login.as_str());
+        writeln!(
+            out,
+            "{}:{line}:1: {author}: {}{note}{status}",
+            first.path,
+            first_line(&first.body)
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommentConnection, ReviewComment, User};
+    use rstest::rstest;
+
+    fn thread(diff_hunk: &str, body: &str) -> ReviewThread {
+        ReviewThread {
+            comments: CommentConnection {
+                nodes: vec![ReviewComment {
+                    body: body.into(),
+                    diff_hunk: diff_hunk.into(),
+                    path: "src/lib.rs".into(),
+                    author: Some(User {
+                        login: "alice".into(),
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn render(threads: &[ReviewThread]) -> String {
+        let mut buf = Vec::new();
+        write_quickfix(&mut buf, threads).expect("write quickfix");
+        String::from_utf8(buf).expect("utf8")
+    }
+
+    #[rstest]
+    #[case("@@ -3,2 +7,2 @@\n ctx\n+new", "src/lib.rs:8:1: alice: Fix this\n")]
+    #[case("not a hunk", "src/lib.rs:1:1: alice: Fix this\n")]
+    fn entry_uses_hunk_line_or_fallback(#[case] hunk: &str, #[case] expected: &str) {
+        assert_eq!(render(&[thread(hunk, "\n  Fix this  \nmore")]), expected);
+    }
+
+    #[rstest]
+    #[case(LocalLine::Present { line: 42 }, "src/lib.rs:42:1: alice: Fix this\n")]
+    #[case(
+        LocalLine::Edited { line: 43 },
+        "src/lib.rs:43:1: alice: Fix this [edited locally]\n"
+    )]
+    #[case(
+        LocalLine::Deleted,
+        "src/lib.rs:8:1: alice: Fix this [deleted locally]\n"
+    )]
+    fn entry_prefers_local_line(#[case] local: LocalLine, #[case] expected: &str) {
+        let mut t = thread("@@ -3,2 +7,2 @@\n ctx\n+new", "Fix this");
+        if let Some(c) = t.comments.nodes.first_mut() {
+            c.local_line = Some(local);
+        }
+        assert_eq!(render(&[t]), expected);
+    }
+
+    #[rstest]
+    #[case(LocalStatus::Untouched, "src/lib.rs:8:1: alice: Fix this\n")]
+    #[case(
+        LocalStatus::ModifiedLocally,
+        "src/lib.rs:8:1: alice: Fix this [modified locally]\n"
+    )]
+    fn entry_notes_changed_threads(#[case] status: LocalStatus, #[case] expected: &str) {
+        let mut t = thread("@@ -3,2 +7,2 @@\n ctx\n+new", "Fix this");
+        t.local_status = Some(status);
+        assert_eq!(render(&[t]), expected);
+    }
+
+    #[test]
+    fn empty_body_and_threads() {
+        let out = render(&[ReviewThread::default(), thread("@@ -1 +1 @@\n+x", "")]);
+        assert_eq!(out, "src/lib.rs:1:1: alice: (no comment)\n");
+    }
+}
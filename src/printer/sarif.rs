@@ -0,0 +1,300 @@
+//! SARIF 2.1.0 rendering of review threads.
+//!
+//! Each thread becomes one result so code-scanning dashboards and editor
+//! SARIF viewers can list unresolved review comments alongside static
+//! analysis findings. The location comes from the first comment's path and
+//! its working-tree line when `--map-local` found one, otherwise the line
+//! resolved by [`comment_line`]. Resolved threads, which only appear when a
+//! discussion link selects them, are reported as notes rather than warnings.
+
+use serde::Serialize;
+
+use crate::ReviewThread;
+use crate::diff::comment_line;
+use crate::worktree::LocalLine;
+
+/// SARIF specification version written to the log.
+pub const SARIF_VERSION: &str = "2.1.0";
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Identifier of the single rule every review comment result refers to.
+pub const REVIEW_COMMENT_RULE: &str = "review-comment";
+
+/// Top-level SARIF log.
+#[derive(Debug, Serialize)]
+pub struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Debug, Serialize)]
+struct Run<'a> {
+    tool: Tool,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: [Rule; 1],
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: &'static str,
+    short_description: Message<'static>,
+}
+
+#[derive(Debug, Serialize)]
+struct Message<'a> {
+    text: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: &'static str,
+    level: &'static str,
+    message: Message<'a>,
+    locations: [Location<'a>; 1],
+    properties: Properties<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location<'a> {
+    physical_location: PhysicalLocation<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation<'a> {
+    artifact_location: ArtifactLocation<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Debug, Serialize)]
+struct ArtifactLocation<'a> {
+    uri: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Properties<'a> {
+    permalink: &'a str,
+    thread_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<&'a str>,
+    replies: usize,
+    is_outdated: bool,
+    resolved: bool,
+}
+
+impl<'a> SarifLog<'a> {
+    /// Build a log with one result per thread.
+    ///
+    /// Threads without comments carry no location and are skipped. The
+    /// region is omitted when no line can be derived from the diff hunk.
+    #[must_use]
+    pub fn new(threads: &'a [ReviewThread]) -> Self {
+        let results = threads.iter().filter_map(thread_result).collect();
+        Self {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: [Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: env!("CARGO_PKG_NAME"),
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: env!("CARGO_PKG_REPOSITORY"),
+                        rules: [Rule {
+                            id: REVIEW_COMMENT_RULE,
+                            short_description: Message {
+                                text: "Pull request review comment",
+                            },
+                        }],
+                    },
+                },
+                results,
+            }],
+        }
+    }
+}
+
+fn thread_result(thread: &ReviewThread) -> Option<SarifResult<'_>> {
+    let first = thread.comments.nodes.first()?;
+    let line = first
+        .local_line
+        .and_then(LocalLine::line)
+        .or_else(|| comment_line(first));
+    Some(SarifResult {
+        rule_id: REVIEW_COMMENT_RULE,
+        level: if thread.is_resolved {
+            "note"
+        } else {
+            "warning"
+        },
+        message: Message { text: &first.body },
+        locations: [Location {
+            physical_location: PhysicalLocation {
+                artifact_location: ArtifactLocation { uri: &first.path },
+                region: line.map(|start_line| Region { start_line }),
+            },
+        }],
+        properties: Properties {
+            permalink: &first.url,
+            thread_id: &thread.id,
+            author: first.author.as_ref().map(|u| u.login.as_str()),
+            replies: thread.comments.nodes.len().saturating_sub(1),
+            is_outdated: thread.is_outdated,
+            resolved: thread.is_resolved,
+        },
+    })
+}
+
+/// Write `log` as pretty-printed JSON followed by a newline.
+///
+/// # Errors
+///
+/// Returns an error if serialisation fails or the writer reports an I/O
+/// error. I/O failures are surfaced as [`std::io::Error`] so callers can
+/// detect broken pipes.
+pub fn write_sarif_log<W: std::io::Write>(mut out: W, log: &SarifLog<'_>) -> anyhow::Result<()> {
+    serde_json::to_writer_pretty(&mut out, log).map_err(std::io::Error::from)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommentConnection, ReviewComment, User};
+    use serde_json::{Value, json};
+
+    fn thread(diff_hunk: &str) -> ReviewThread {
+        let first = ReviewComment {
+            body: "Please rename".into(),
+            diff_hunk: diff_hunk.into(),
+            position: Some(11),
+            path: "src/lib.rs".into(),
+            url: "https://example.com#discussion_r1".into(),
+            author: Some(User {
+                login: "alice".into(),
+            }),
+            ..Default::default()
+        };
+        let reply = ReviewComment {
+            body: "Done".into(),
+            ..Default::default()
+        };
+        ReviewThread {
+            id: "T1".into(),
+            comments: CommentConnection {
+                nodes: vec![first, reply],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn render(threads: &[ReviewThread]) -> Value {
+        let mut buf = Vec::new();
+        write_sarif_log(&mut buf, &SarifLog::new(threads)).expect("write sarif");
+        serde_json::from_slice(&buf).expect("parse sarif")
+    }
+
+    #[test]
+    fn thread_maps_to_result() {
+        let value = render(&[thread("@@ -10,2 +10,2 @@\n ctx\n-old\n+new")]);
+        assert_eq!(value.pointer("/version"), Some(&json!("2.1.0")));
+        assert_eq!(
+            value.pointer("/runs/0/tool/driver/name"),
+            Some(&json!("vk"))
+        );
+        let result = value.pointer("/runs/0/results/0").expect("result");
+        assert_eq!(result.pointer("/ruleId"), Some(&json!("review-comment")));
+        assert_eq!(
+            result.pointer("/message/text"),
+            Some(&json!("Please rename"))
+        );
+        assert_eq!(
+            result.pointer("/locations/0/physicalLocation/artifactLocation/uri"),
+            Some(&json!("src/lib.rs"))
+        );
+        assert_eq!(
+            result.pointer("/locations/0/physicalLocation/region/startLine"),
+            Some(&json!(11))
+        );
+        assert_eq!(
+            result.pointer("/properties/permalink"),
+            Some(&json!("https://example.com#discussion_r1"))
+        );
+        assert_eq!(result.pointer("/properties/replies"), Some(&json!(1)));
+        assert_eq!(result.pointer("/level"), Some(&json!("warning")));
+        assert_eq!(result.pointer("/properties/resolved"), Some(&json!(false)));
+    }
+
+    #[test]
+    fn resolved_threads_are_notes() {
+        let mut resolved = thread("@@ -10,2 +10,2 @@\n ctx\n-old\n+new");
+        resolved.is_resolved = true;
+        let value = render(&[resolved]);
+        let result = value.pointer("/runs/0/results/0").expect("result");
+        assert_eq!(result.pointer("/level"), Some(&json!("note")));
+        assert_eq!(result.pointer("/properties/resolved"), Some(&json!(true)));
+    }
+
+    #[test]
+    fn mapped_lines_replace_pull_request_lines() {
+        let mut mapped = thread("@@ -10,2 +10,2 @@\n ctx\n-old\n+new");
+        if let Some(first) = mapped.comments.nodes.first_mut() {
+            first.local_line = Some(LocalLine::Edited { line: 14 });
+        }
+        let mut deleted = thread("@@ -10,2 +10,2 @@\n ctx\n-old\n+new");
+        if let Some(first) = deleted.comments.nodes.first_mut() {
+            first.local_line = Some(LocalLine::Deleted);
+        }
+        let value = render(&[mapped, deleted]);
+        let line = |i: usize| {
+            value.pointer(&format!(
+                "/runs/0/results/{i}/locations/0/physicalLocation/region/startLine"
+            ))
+        };
+        assert_eq!(line(0), Some(&json!(14)));
+        assert_eq!(line(1), Some(&json!(11)));
+    }
+
+    #[test]
+    fn region_is_omitted_without_line_numbers() {
+        let value = render(&[thread("not a hunk")]);
+        assert_eq!(
+            value.pointer("/runs/0/results/0/locations/0/physicalLocation/region"),
+            None
+        );
+    }
+
+    #[test]
+    fn empty_threads_are_skipped() {
+        let value = render(&[ReviewThread::default()]);
+        assert_eq!(value.pointer("/runs/0/results"), Some(&json!([])));
+    }
+}
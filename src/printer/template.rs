@@ -0,0 +1,482 @@
+//! User-defined output templates.
+//!
+//! `--template` renders the review state with a [MiniJinja] template instead
+//! of a fixed layout. A template is named either by a path or by a name
+//! looked up as `<name>.jinja` in `vk/templates` under the configuration
+//! directory. The built-in `default` template reproduces the banner-framed
+//! Markdown layout and is a starting point for custom ones.
+//!
+//! Templates see the repository and pull request number, the file summary,
+//! the latest reviews and the selected threads, each thread carrying its
+//! path, diff excerpt and comments. Comment bodies are prepared as they are
+//! for Markdown output unless `--raw-body` is set.
+//!
+//! [MiniJinja]: https://docs.rs/minijinja
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use minijinja::Environment;
+use serde::Serialize;
+
+use super::markdown::fence_for;
+use super::{Formattable, RawComment, reactions_line, resolution_note};
+use crate::cli_args::{DiffContext, TimestampStyle};
+use crate::diff::{comment_diff_excerpt, comment_line};
+use crate::ref_parser::RepoInfo;
+use crate::reviews::PullRequestReview;
+use crate::timestamps::describe_time;
+use crate::worktree::local_note;
+use crate::{ReviewComment, ReviewThread, VkError};
+use vk::banners::{COMMENTS_BANNER, END_BANNER, START_BANNER};
+use vk::icons::Icons;
+
+/// Name of the built-in template.
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+/// Extension of templates looked up by name.
+const TEMPLATE_EXTENSION: &str = "jinja";
+
+/// Source of the built-in template.
+const DEFAULT_SOURCE: &str = include_str!("templates/default.jinja");
+
+/// Label used for comments and reviews whose author is unknown.
+const UNKNOWN_AUTHOR: &str = "(unknown)";
+
+/// Directory searched for templates named without a path: `vk/templates`
+/// under `XDG_CONFIG_HOME`, `~/.config` or, on Windows, `APPDATA`.
+///
+/// Variables are looked up with `var`.
+#[must_use]
+pub fn templates_dir(var: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let set = |name: &str| var(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let base = set("XDG_CONFIG_HOME")
+        .or_else(|| set("HOME").map(|home| home.join(".config")))
+        .or_else(|| set("APPDATA"))?;
+    Some(base.join("vk").join("templates"))
+}
+
+/// A parsed output template and the options used to fill it.
+#[derive(Debug)]
+pub struct OutputTemplate {
+    name: String,
+    source: Cow<'static, str>,
+    context: DiffContext,
+    raw_body: bool,
+    icons: Icons,
+    timestamps: TimestampStyle,
+}
+
+impl OutputTemplate {
+    /// Load the template `spec`, a path or a name looked up in `dir`.
+    ///
+    /// An existing file wins over a template of the same name in `dir`, and
+    /// `default` names the built-in template unless `dir` overrides it. The
+    /// template is parsed here, so syntax errors surface before any request
+    /// is made.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VkError::TemplateNotFound`] when `spec` names no file or
+    /// template, [`VkError::Io`] when the file cannot be read, and
+    /// [`VkError::Template`] when it does not parse.
+    pub fn load(spec: &str, dir: Option<&Path>) -> Result<Self, VkError> {
+        let path = Path::new(spec);
+        let named = dir.map(|d| d.join(format!("{spec}.{TEMPLATE_EXTENSION}")));
+        let found = if path.is_file() {
+            Some(path.to_path_buf())
+        } else {
+            named.filter(|p| p.is_file())
+        };
+        let (name, source) = match found {
+            Some(file) => {
+                let name = file
+                    .file_name()
+                    .map_or_else(|| spec.to_owned(), |n| n.to_string_lossy().into_owned());
+                (name, Cow::Owned(std::fs::read_to_string(&file)?))
+            }
+            None if spec == DEFAULT_TEMPLATE => {
+                (DEFAULT_TEMPLATE.to_owned(), Cow::Borrowed(DEFAULT_SOURCE))
+            }
+            None => return Err(VkError::TemplateNotFound { name: spec.into() }),
+        };
+        let template = Self {
+            name,
+            source,
+            context: DiffContext::default(),
+            raw_body: false,
+            icons: Icons::default(),
+            timestamps: TimestampStyle::default(),
+        };
+        template.environment().map_err(|e| template_error(&e))?;
+        Ok(template)
+    }
+
+    /// Show `context` lines around each commented line in diff excerpts.
+    #[must_use]
+    pub const fn with_context(mut self, context: DiffContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Pass comment bodies exactly as posted instead of extracting bot
+    /// comments and collapsing `<details>` blocks.
+    #[must_use]
+    pub const fn with_raw_body(mut self, raw_body: bool) -> Self {
+        self.raw_body = raw_body;
+        self
+    }
+
+    /// Mark collapsed blocks and reactions with the glyphs from `icons`.
+    #[must_use]
+    pub const fn with_icons(mut self, icons: Icons) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// Describe posting times in the given style.
+    #[must_use]
+    pub const fn with_timestamps(mut self, timestamps: TimestampStyle) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
+
+    /// Build an environment holding the parsed template.
+    ///
+    /// Block tags swallow the newline that follows them and the whitespace
+    /// before them, so templates can put each tag on its own line. Templates
+    /// whose name ends in `.html` escape HTML in substituted values.
+    fn environment(&self) -> Result<Environment<'_>, minijinja::Error> {
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_keep_trailing_newline(true);
+        env.add_template(&self.name, &self.source)?;
+        Ok(env)
+    }
+
+    /// Render the template for pull request `number` in `repo`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VkError::Template`] when rendering fails, such as when the
+    /// template calls an unknown filter.
+    pub fn render(
+        &self,
+        repo: &RepoInfo,
+        number: u64,
+        summary: &[(String, usize)],
+        reviews: &[PullRequestReview],
+        threads: &[ReviewThread],
+    ) -> Result<String, VkError> {
+        let context = TemplateContext {
+            repo: format!("{}/{}", repo.owner, repo.name),
+            owner: &repo.owner,
+            name: &repo.name,
+            number,
+            url: format!(
+                "https://github.com/{}/{}/pull/{number}",
+                repo.owner, repo.name
+            ),
+            banners: Banners {
+                start: START_BANNER,
+                comments: COMMENTS_BANNER,
+                end: END_BANNER,
+            },
+            summary: summary
+                .iter()
+                .map(|(path, comments)| FileView {
+                    path,
+                    comments: *comments,
+                })
+                .collect(),
+            reviews: reviews.iter().map(|r| self.review_view(r)).collect(),
+            threads: threads
+                .iter()
+                .map(|t| self.thread_view(t))
+                .collect::<Result<_, _>>()
+                .map_err(|e| VkError::Template(e.to_string().into()))?,
+        };
+        let env = self.environment().map_err(|e| template_error(&e))?;
+        env.get_template(&self.name)
+            .and_then(|t| t.render(&context))
+            .map_err(|e| template_error(&e))
+    }
+
+    fn review_view<'a>(&self, review: &'a PullRequestReview) -> ReviewView<'a> {
+        ReviewView {
+            author: review.author_login().unwrap_or(UNKNOWN_AUTHOR),
+            state: &review.state,
+            body: review.body(self.icons).trim_end().to_owned(),
+            submitted_at: review.submitted_at.map(|t| t.to_rfc3339()),
+            posted: review
+                .submitted_at
+                .map(|t| describe_time(t, Utc::now(), self.timestamps)),
+        }
+    }
+
+    fn thread_view<'a>(&self, thread: &'a ReviewThread) -> Result<ThreadView<'a>, std::fmt::Error> {
+        let first = thread.comments.nodes.first();
+        let (diff_kind, diff) = match first {
+            Some(c) => match &c.head_context {
+                Some(head) => ("text", head.format()?),
+                None => ("diff", comment_diff_excerpt(c, self.context)?),
+            },
+            None => ("diff", String::new()),
+        };
+        let comments = thread
+            .comments
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, c)| self.comment_view(c, (i == 0).then_some(thread)))
+            .collect();
+        Ok(ThreadView {
+            path: first.map_or("", |c| c.path.as_str()),
+            line: first.and_then(comment_line),
+            url: first.map_or("", |c| c.url.as_str()),
+            author: first
+                .and_then(Formattable::author_login)
+                .unwrap_or(UNKNOWN_AUTHOR),
+            fence: fence_for(&diff),
+            diff_kind,
+            diff,
+            resolved: thread.is_resolved,
+            resolved_by: thread.resolved_by.as_ref().map(|u| u.login.as_str()),
+            resolution: resolution_note(thread),
+            comments,
+        })
+    }
+
+    /// Describe `comment`; the first comment of a thread receives the
+    /// `thread` so its local note includes the thread's local status.
+    fn comment_view<'a>(
+        &self,
+        comment: &'a ReviewComment,
+        thread: Option<&ReviewThread>,
+    ) -> CommentView<'a> {
+        let body = if self.raw_body {
+            RawComment(comment).body(self.icons).into_owned()
+        } else {
+            comment.body(self.icons).into_owned()
+        };
+        CommentView {
+            author: comment.author_login().unwrap_or(UNKNOWN_AUTHOR),
+            body: body.trim_end().to_owned(),
+            raw_body: &comment.body,
+            url: &comment.url,
+            path: &comment.path,
+            line: comment_line(comment),
+            created_at: comment.created_at.map(|t| t.to_rfc3339()),
+            posted: comment
+                .created_at
+                .map(|t| describe_time(t, Utc::now(), self.timestamps)),
+            edited: comment.edited(),
+            local: local_note(comment, thread.and_then(|t| t.local_status)),
+            reactions: reactions_line(&comment.reactions, self.icons),
+        }
+    }
+}
+
+fn template_error(err: &minijinja::Error) -> VkError {
+    VkError::Template(err.to_string().into())
+}
+
+/// Variables available to templates.
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    /// `owner/name` of the repository.
+    repo: String,
+    owner: &'a str,
+    name: &'a str,
+    number: u64,
+    /// Web URL of the pull request.
+    url: String,
+    banners: Banners,
+    summary: Vec<FileView<'a>>,
+    reviews: Vec<ReviewView<'a>>,
+    threads: Vec<ThreadView<'a>>,
+}
+
+#[derive(Serialize)]
+struct Banners {
+    start: &'static str,
+    comments: &'static str,
+    end: &'static str,
+}
+
+#[derive(Serialize)]
+struct FileView<'a> {
+    path: &'a str,
+    comments: usize,
+}
+
+#[derive(Serialize)]
+struct ReviewView<'a> {
+    author: &'a str,
+    state: &'a str,
+    body: String,
+    /// RFC 3339 submission time.
+    submitted_at: Option<String>,
+    /// Submission time in the `--timestamps` style.
+    posted: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ThreadView<'a> {
+    path: &'a str,
+    line: Option<u32>,
+    /// Permalink of the first comment.
+    url: &'a str,
+    /// Login of the first comment's author.
+    author: &'a str,
+    /// Diff excerpt, or head-commit lines with `--expand-context`.
+    diff: String,
+    /// `diff` or `text`, naming the kind of excerpt for code fences.
+    diff_kind: &'static str,
+    /// Backtick fence longer than any run in `diff`.
+    fence: String,
+    resolved: bool,
+    resolved_by: Option<&'a str>,
+    /// `Resolved by <login>` for resolved threads.
+    resolution: Option<String>,
+    comments: Vec<CommentView<'a>>,
+}
+
+#[derive(Serialize)]
+struct CommentView<'a> {
+    author: &'a str,
+    /// Body prepared for display, or as posted with `--raw-body`.
+    body: String,
+    raw_body: &'a str,
+    url: &'a str,
+    path: &'a str,
+    line: Option<u32>,
+    /// RFC 3339 posting time.
+    created_at: Option<String>,
+    /// Posting time in the `--timestamps` style.
+    posted: Option<String>,
+    edited: bool,
+    /// Working-tree location and status from `--map-local` and
+    /// `--local-status`.
+    local: Option<String>,
+    /// Reaction glyphs and counts on one line.
+    reactions: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::printer::markdown::MarkdownThreadWriter;
+    use crate::summary::summarize_files;
+    use crate::{CommentConnection, User};
+    use std::io::Write;
+
+    fn repo() -> RepoInfo {
+        RepoInfo {
+            owner: "o".into(),
+            name: "r".into(),
+        }
+    }
+
+    fn thread(path: &str, body: &str) -> ReviewThread {
+        let comment = ReviewComment {
+            path: path.into(),
+            body: body.into(),
+            diff_hunk: "@@ -1 +1 @@\n-old\n+new".into(),
+            url: format!("https://example.com/{path}"),
+            author: Some(User {
+                login: "alice".into(),
+            }),
+            ..Default::default()
+        };
+        ReviewThread {
+            comments: CommentConnection {
+                nodes: vec![comment],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn write_template(dir: &Path, name: &str, source: &str) {
+        let mut file =
+            std::fs::File::create(dir.join(format!("{name}.jinja"))).expect("create template");
+        file.write_all(source.as_bytes()).expect("write template");
+    }
+
+    #[test]
+    fn default_template_matches_markdown_layout() {
+        let threads = vec![
+            thread("a.rs", "Fix"),
+            thread("a.rs", "Also"),
+            thread("b.rs", "Hm"),
+        ];
+        let template = OutputTemplate::load(DEFAULT_TEMPLATE, None).expect("built-in");
+        let out = template
+            .render(&repo(), 1, &summarize_files(&threads), &[], &threads)
+            .expect("render");
+
+        let mut expected = Vec::new();
+        crate::summary::write_start_banner(&mut expected).expect("banner");
+        crate::summary::write_summary(&mut expected, &summarize_files(&threads)).expect("summary");
+        crate::summary::write_comments_banner(&mut expected).expect("banner");
+        let mut writer = MarkdownThreadWriter::new(DiffContext::default());
+        for t in &threads {
+            writer.write_thread(&mut expected, t).expect("thread");
+        }
+        crate::summary::write_end_banner(&mut expected).expect("banner");
+        assert_eq!(out, String::from_utf8(expected).expect("utf8"));
+    }
+
+    #[test]
+    fn templates_are_found_by_name() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        write_template(
+            dir.path(),
+            "brief",
+            "{{ repo }}#{{ number }}\n{% for t in threads %}{{ t.path }}:{{ t.line }} {{ t.author }}: \
+             {{ t.comments | map(attribute='body') | join(' / ') }}\n{% endfor %}",
+        );
+        let template = OutputTemplate::load("brief", Some(dir.path())).expect("found");
+        let out = template
+            .render(&repo(), 7, &[], &[], &[thread("a.rs", "Fix")])
+            .expect("render");
+        assert_eq!(out, "o/r#7\na.rs:1 alice: Fix\n");
+    }
+
+    #[test]
+    fn missing_templates_are_reported() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let err = OutputTemplate::load("nope", Some(dir.path())).expect_err("missing");
+        assert!(matches!(err, VkError::TemplateNotFound { .. }));
+    }
+
+    #[test]
+    fn syntax_errors_surface_on_load() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        write_template(dir.path(), "broken", "{% for t in threads %}");
+        let err = OutputTemplate::load("broken", Some(dir.path())).expect_err("broken");
+        assert!(matches!(err, VkError::Template(_)));
+    }
+
+    #[test]
+    fn templates_dir_prefers_xdg() {
+        let env = |xdg: Option<&'static str>| {
+            move |name: &str| match name {
+                "XDG_CONFIG_HOME" => xdg.map(str::to_owned),
+                "HOME" => Some("/home/u".to_owned()),
+                _ => None,
+            }
+        };
+        assert_eq!(
+            templates_dir(env(Some("/cfg"))),
+            Some(PathBuf::from("/cfg/vk/templates"))
+        );
+        assert_eq!(
+            templates_dir(env(None)),
+            Some(PathBuf::from("/home/u/.config/vk/templates"))
+        );
+    }
+}
@@ -4,12 +4,16 @@ use super::*;
 
 use chrono::Utc;
 use rstest::rstest;
+use termimad::crossterm::style::Color;
 
 use crate::{
     CommentConnection, ReviewComment, ReviewThread, User,
+    review_threads::{ReactionContent, ReactorCount},
     test_utils::{
         assert_diff_lines_not_blank_separated, assert_no_triple_newlines, strip_ansi_codes,
     },
+    theme::Theme,
+    worktree::LocalLine,
 };
 use vk::icons::{ICON_COMMENT, ICON_FILE, ICON_PERMALINK, ICON_REVIEW};
 
@@ -58,7 +62,7 @@ fn print_reviews_formats_authors_and_states() {
     ];
     let skin = MadSkin::default();
     let mut buf = Vec::new();
-    print_reviews(&mut buf, &skin, &reviews).expect("print reviews");
+    print_reviews(&mut buf, &skin, &reviews, &RenderOptions::default()).expect("print reviews");
     let out = String::from_utf8(buf).expect("utf8");
     assert!(out.contains("alice"));
     assert!(out.contains("(unknown)"));
@@ -81,7 +85,13 @@ fn write_review_formats_banner(
         author: login.map(|l| User { login: l.into() }),
     };
     let mut buf = Vec::new();
-    write_review(&mut buf, &MadSkin::default(), &review).expect("write review");
+    write_review(
+        &mut buf,
+        &MadSkin::default(),
+        &review,
+        &RenderOptions::default(),
+    )
+    .expect("write review");
     let out = String::from_utf8(buf).expect("utf8");
     assert!(out.contains(expected_login));
     assert!(out.contains(state));
@@ -98,7 +108,13 @@ fn write_review_collapses_details() {
         author: None,
     };
     let mut buf = Vec::new();
-    write_review(&mut buf, &MadSkin::default(), &review).expect("write review");
+    write_review(
+        &mut buf,
+        &MadSkin::default(),
+        &review,
+        &RenderOptions::default(),
+    )
+    .expect("write review");
     let out = String::from_utf8(buf).expect("utf8");
     assert!(out.contains("▶ sum"));
     assert!(!out.contains("hidden"));
@@ -114,7 +130,13 @@ fn write_comment_body_formats_banner(#[case] login: Option<&str>, #[case] expect
         ..Default::default()
     };
     let mut buf = Vec::new();
-    write_comment_body(&mut buf, &MadSkin::default(), &comment).expect("write comment");
+    write_comment_body(
+        &mut buf,
+        &MadSkin::default(),
+        &comment,
+        &RenderOptions::default(),
+    )
+    .expect("write comment");
     let out = String::from_utf8(buf).expect("utf8");
     assert!(out.contains(expected_login));
     assert!(out.contains("wrote"));
@@ -129,7 +151,13 @@ fn write_comment_body_collapses_details() {
         ..Default::default()
     };
     let mut buf = Vec::new();
-    write_comment_body(&mut buf, &MadSkin::default(), &comment).expect("write comment");
+    write_comment_body(
+        &mut buf,
+        &MadSkin::default(),
+        &comment,
+        &RenderOptions::default(),
+    )
+    .expect("write comment");
     let out = String::from_utf8(buf).expect("utf8");
     assert!(out.contains("▶ sum"));
     assert!(!out.contains("hidden"));
@@ -154,7 +182,13 @@ fn write_comment_body_renders_coderabbit_comment() {
         ..Default::default()
     };
     let mut buf = Vec::new();
-    write_comment_body(&mut buf, &MadSkin::default(), &comment).expect("write comment");
+    write_comment_body(
+        &mut buf,
+        &MadSkin::default(),
+        &comment,
+        &RenderOptions::default(),
+    )
+    .expect("write comment");
     let out = String::from_utf8(buf).expect("utf8");
     let plain = strip_ansi_codes(&out);
     assert_no_triple_newlines(&plain);
@@ -172,7 +206,13 @@ fn write_thread_emits_structured_layout_per_comment() {
         diff_comment("Second", "https://example.com#discussion_r2"),
     ]);
     let mut buf = Vec::new();
-    write_thread(&mut buf, &MadSkin::default(), &thread).expect("write thread");
+    write_thread(
+        &mut buf,
+        &MadSkin::default(),
+        &thread,
+        &RenderOptions::default(),
+    )
+    .expect("write thread");
     let out = strip_ansi_codes(&String::from_utf8(buf).expect("utf8"));
 
     let url1_banner = format!("{ICON_PERMALINK} https://example.com#discussion_r1");
@@ -209,7 +249,13 @@ fn write_thread_frames_each_comment_with_single_blank_before_separator() {
         "https://example.com#discussion_r1",
     )]);
     let mut buf = Vec::new();
-    write_thread(&mut buf, &MadSkin::default(), &thread).expect("write thread");
+    write_thread(
+        &mut buf,
+        &MadSkin::default(),
+        &thread,
+        &RenderOptions::default(),
+    )
+    .expect("write thread");
     let out = strip_ansi_codes(&String::from_utf8(buf).expect("utf8"));
     assert_no_triple_newlines(&out);
     // A single blank line precedes the closing thematic break.
@@ -219,11 +265,246 @@ fn write_thread_frames_each_comment_with_single_blank_before_separator() {
     );
 }
 
+#[rstest]
+#[case(LocalLine::Present { line: 42 }, "src/lib.rs: (local src/lib.rs:42)")]
+#[case(LocalLine::Edited { line: 42 }, "src/lib.rs: (local src/lib.rs:42, edited)")]
+#[case(LocalLine::Deleted, "src/lib.rs: (lines deleted locally)")]
+fn write_thread_shows_local_line(#[case] local: LocalLine, #[case] expected: &str) {
+    let mut comment = diff_comment("Only", "https://example.com#discussion_r1");
+    comment.local_line = Some(local);
+    let mut buf = Vec::new();
+    write_thread(
+        &mut buf,
+        &MadSkin::default(),
+        &thread_with(vec![comment]),
+        &RenderOptions::default(),
+    )
+    .expect("write thread");
+    let out = strip_ansi_codes(&String::from_utf8(buf).expect("utf8"));
+    assert!(
+        out.contains(&format!("{ICON_FILE} {expected}\n")),
+        "missing local line: {out:?}"
+    );
+}
+
+#[test]
+fn ascii_icons_replace_emoji() {
+    let comment = diff_comment(
+        "<details><summary>More</summary>hidden</details>",
+        "https://example.com#discussion_r1",
+    );
+    let mut buf = Vec::new();
+    write_thread(
+        &mut buf,
+        &Theme::default().skin(Icons::ASCII),
+        &thread_with(vec![comment]),
+        &RenderOptions {
+            icons: Icons::ASCII,
+            ..RenderOptions::default()
+        },
+    )
+    .expect("write thread");
+    let out = strip_ansi_codes(&String::from_utf8(buf).expect("utf8"));
+    assert!(out.is_ascii(), "non-ASCII output: {out}");
+    assert!(out.contains("[link] https://example.com#discussion_r1\n"));
+    assert!(out.contains("[file] src/lib.rs:\n"));
+    assert!(out.contains("[comment]  (unknown) wrote:"));
+    assert!(out.contains("> More"));
+}
+
+#[test]
+fn author_colour_applies_to_banner() {
+    let comment = ReviewComment {
+        body: "hi".into(),
+        author: Some(User {
+            login: "alice".into(),
+        }),
+        ..Default::default()
+    };
+    let options = RenderOptions {
+        palette: Palette {
+            author: Some(Color::DarkBlue),
+            ..Palette::default()
+        },
+        ..RenderOptions::default()
+    };
+    let mut buf = Vec::new();
+    write_comment_body(&mut buf, &MadSkin::default(), &comment, &options).expect("write comment");
+    let out = String::from_utf8(buf).expect("utf8");
+    assert!(
+        out.starts_with(&format!(
+            "{ICON_COMMENT}  \x1b[1m\x1b[34malice\x1b[0m wrote:"
+        )),
+        "unexpected banner: {out:?}"
+    );
+}
+
+#[rstest]
+#[case(TimestampStyle::Relative, "alice\x1b[0m wrote 3 days ago (edited):")]
+#[case(
+    TimestampStyle::Absolute,
+    "alice\x1b[0m wrote on 2025-01-03 14:05 UTC (edited):"
+)]
+fn banner_shows_posting_time_and_edits(#[case] style: TimestampStyle, #[case] expected: &str) {
+    let created = match style {
+        TimestampStyle::Relative => Utc::now() - chrono::Duration::days(3),
+        TimestampStyle::Absolute => "2025-01-03T14:05:00Z".parse().expect("timestamp"),
+    };
+    let comment = ReviewComment {
+        body: "hi".into(),
+        author: Some(User {
+            login: "alice".into(),
+        }),
+        created_at: Some(created),
+        last_edited_at: Some(created + chrono::Duration::hours(1)),
+        ..Default::default()
+    };
+    let options = RenderOptions {
+        timestamps: style,
+        ..RenderOptions::default()
+    };
+    let mut buf = Vec::new();
+    write_comment_body(&mut buf, &MadSkin::default(), &comment, &options).expect("write comment");
+    let out = String::from_utf8(buf).expect("utf8");
+    assert!(out.contains(expected), "unexpected banner: {out:?}");
+}
+
+#[test]
+fn ascii_reactions_use_shortcodes() {
+    let reactions = [
+        ReactionGroup {
+            content: ReactionContent::ThumbsUp,
+            reactors: ReactorCount { total_count: 3 },
+        },
+        ReactionGroup {
+            content: ReactionContent::ThumbsDown,
+            reactors: ReactorCount { total_count: 1 },
+        },
+    ];
+    assert_eq!(
+        reactions_line(&reactions, Icons::ASCII).as_deref(),
+        Some(":+1: 3  :-1: 1")
+    );
+    assert_eq!(reactions_line(&[], Icons::EMOJI), None);
+}
+
+#[test]
+fn links_wrap_permalink_path_and_author() {
+    let mut comment = diff_comment("hi", "https://github.com/o/r/pull/1#discussion_r1");
+    comment.author = Some(User {
+        login: "alice".into(),
+    });
+    let mut buf = Vec::new();
+    write_thread(
+        &mut buf,
+        &MadSkin::default(),
+        &thread_with(vec![comment]),
+        &RenderOptions {
+            links: Some(LinkTargets::at_head("o", "r", "abc")),
+            ..RenderOptions::default()
+        },
+    )
+    .expect("write thread");
+    let out = String::from_utf8(buf).expect("utf8");
+    let url = "https://github.com/o/r/pull/1#discussion_r1";
+    for link in [
+        osc8(url, url),
+        osc8("https://github.com/o/r/blob/abc/src/lib.rs", "src/lib.rs"),
+        osc8("https://github.com/alice", "alice"),
+    ] {
+        assert!(out.contains(&link), "missing {link:?}: {out:?}");
+    }
+}
+
+#[test]
+fn search_matches_are_highlighted_outside_escapes() {
+    let comment = diff_comment(
+        "avoid unwrap",
+        "https://github.com/o/r/pull/1#discussion_r1",
+    );
+    let mut buf = Vec::new();
+    write_thread(
+        &mut buf,
+        &MadSkin::default(),
+        &thread_with(vec![comment]),
+        &RenderOptions {
+            links: Some(LinkTargets::at_head("o", "r", "abc")),
+            matches: Some(Regex::new("unwrap|lib").expect("valid regex")),
+            ..RenderOptions::default()
+        },
+    )
+    .expect("write thread");
+    let out = String::from_utf8(buf).expect("utf8");
+    assert!(out.contains("\x1b[7munwrap\x1b[27m"), "{out:?}");
+    assert!(out.contains("src/\x1b[7mlib\x1b[27m.rs"), "{out:?}");
+    assert!(
+        out.contains("https://github.com/o/r/blob/abc/src/lib.rs"),
+        "link target altered: {out:?}"
+    );
+}
+
+#[rstest]
+#[case(false, "Suggested fix", "<details>")]
+#[case(true, "<details>", "Suggested fix")]
+fn raw_body_bypasses_bot_extraction(
+    #[case] raw_body: bool,
+    #[case] present: &str,
+    #[case] absent: &str,
+) {
+    let mut comment = diff_comment(CODERABBIT_COMMENT, "https://example.com#discussion_r1");
+    comment.author = Some(User {
+        login: "coderabbitai".into(),
+    });
+    let mut buf = Vec::new();
+    write_thread(
+        &mut buf,
+        &MadSkin::default(),
+        &thread_with(vec![comment]),
+        &RenderOptions {
+            raw_body,
+            ..RenderOptions::default()
+        },
+    )
+    .expect("write thread");
+    let out = strip_ansi_codes(&String::from_utf8(buf).expect("utf8"));
+    assert!(out.contains(present), "missing {present}: {out}");
+    assert!(!out.contains(absent), "unexpected {absent}: {out}");
+}
+
+#[test]
+fn ascii_icons_strip_bot_emoji() {
+    let mut comment = diff_comment(CODERABBIT_COMMENT, "https://example.com#discussion_r1");
+    comment.author = Some(User {
+        login: "coderabbitai".into(),
+    });
+    let mut buf = Vec::new();
+    write_thread(
+        &mut buf,
+        &MadSkin::default(),
+        &thread_with(vec![comment]),
+        &RenderOptions {
+            icons: Icons::ASCII,
+            ..RenderOptions::default()
+        },
+    )
+    .expect("write thread");
+    let out = strip_ansi_codes(&String::from_utf8(buf).expect("utf8"));
+    assert!(out.contains("Potential issue"), "{out}");
+    let glyphs: String = out.chars().filter(|c| !c.is_ascii()).collect();
+    assert_eq!(glyphs, "", "{out}");
+}
+
 #[test]
 fn write_thread_with_no_comments_produces_no_output() {
     let thread = thread_with(Vec::new());
     let mut buf = Vec::new();
-    write_thread(&mut buf, &MadSkin::default(), &thread).expect("write thread");
+    write_thread(
+        &mut buf,
+        &MadSkin::default(),
+        &thread,
+        &RenderOptions::default(),
+    )
+    .expect("write thread");
     assert!(
         buf.is_empty(),
         "empty thread must produce no output: {buf:?}"
@@ -239,7 +520,8 @@ fn print_reviews_propagates_writer_errors() {
         author: None,
     };
     let skin = MadSkin::default();
-    let err = print_reviews(FailWriter, &skin, &[review]).expect_err("should fail");
+    let err = print_reviews(FailWriter, &skin, &[review], &RenderOptions::default())
+        .expect_err("should fail");
     assert!(err.downcast_ref::<std::io::Error>().is_some());
 }
 
@@ -257,7 +539,8 @@ fn write_thread_propagates_writer_errors() {
         ..Default::default()
     };
     let skin = MadSkin::default();
-    let err = write_thread(FailWriter, &skin, &thread).expect_err("should fail");
+    let err = write_thread(FailWriter, &skin, &thread, &RenderOptions::default())
+        .expect_err("should fail");
     assert!(err.downcast_ref::<std::io::Error>().is_some());
 }
 
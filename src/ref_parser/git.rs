@@ -120,3 +120,75 @@ pub(crate) fn repo_from_origin_impl(dir: Option<&Path>) -> Option<RepoInfo> {
 pub fn repo_from_origin() -> Option<RepoInfo> {
     repo_from_origin_impl(None)
 }
+
+/// Read `path`, relative to the repository root, as it was at `rev`.
+///
+/// Runs `git show <rev>:<path>` inside `dir`. Returns `None` when the commit
+/// is not available locally, the file does not exist at that revision, or
+/// its contents are not UTF-8.
+pub fn file_at_revision(dir: &Path, rev: &str, path: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("show")
+        .arg(format!("{rev}:{path}"))
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        // The commit has not been fetched or the file does not exist there
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Internal implementation of worktree root detection that accepts an
+/// optional directory.
+///
+/// When `dir` is `Some`, runs git in that directory; otherwise uses the current
+/// working directory.
+pub(crate) fn worktree_root_impl(dir: Option<&Path>) -> Option<std::path::PathBuf> {
+    let mut cmd = Command::new("git");
+    cmd.args(["rev-parse", "--show-toplevel"]);
+    if let Some(d) = dir {
+        cmd.current_dir(d);
+    }
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let root = String::from_utf8(output.stdout).ok()?;
+    Some(std::path::PathBuf::from(root.trim()))
+}
+
+/// Locate the top-level directory of the current working tree.
+///
+/// Returns `None` outside a Git working tree.
+pub fn worktree_root() -> Option<std::path::PathBuf> {
+    worktree_root_impl(None)
+}
+
+/// Internal implementation of working directory prefix detection that
+/// accepts an optional directory.
+///
+/// When `dir` is `Some`, runs git in that directory; otherwise uses the current
+/// working directory.
+pub(crate) fn worktree_prefix_impl(dir: Option<&Path>) -> Option<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(["rev-parse", "--show-prefix"]);
+    if let Some(d) = dir {
+        cmd.current_dir(d);
+    }
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let prefix = String::from_utf8(output.stdout).ok()?;
+    Some(prefix.trim_end_matches('\n').to_string())
+}
+
+/// Locate the current directory relative to the top of the working tree.
+///
+/// Returns a path such as `src/api/` with a trailing slash, an empty string at
+/// the top level, and `None` outside a Git working tree.
+pub fn worktree_prefix() -> Option<String> {
+    worktree_prefix_impl(None)
+}
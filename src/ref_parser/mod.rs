@@ -7,9 +7,15 @@ mod parse;
 #[cfg(test)]
 mod tests;
 
-pub use git::{current_branch, repo_from_fetch_head, repo_from_origin};
+pub use git::{
+    current_branch, file_at_revision, repo_from_fetch_head, repo_from_origin, worktree_prefix,
+    worktree_root,
+};
 #[cfg(test)]
-pub(crate) use git::{current_branch_impl, repo_from_fetch_head_impl, repo_from_origin_impl};
+pub(crate) use git::{
+    current_branch_impl, repo_from_fetch_head_impl, repo_from_origin_impl, worktree_prefix_impl,
+    worktree_root_impl,
+};
 
 use parse::{GITHUB_RE, ResourceType, parse_reference, strip_git_suffix};
 
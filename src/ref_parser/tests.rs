@@ -271,3 +271,44 @@ fn parse_issue_number_prefers_fetch_head_over_origin() {
     assert_eq!(repo.owner, "upstream");
     assert_eq!(repo.name, "repo");
 }
+
+#[test]
+fn file_at_revision_reads_committed_contents() {
+    let fixture = GitRepoFixture::on_branch("main")
+        .and_then(|f| f.with_committed_file("src/lib.rs", "one\ntwo\n"))
+        .expect("build committed fixture");
+    let oid = fixture.head_oid().expect("head oid");
+    std::fs::write(fixture.path().join("src/lib.rs"), "changed\n").expect("edit file");
+
+    assert_eq!(
+        file_at_revision(fixture.path(), &oid, "src/lib.rs").as_deref(),
+        Some("one\ntwo\n")
+    );
+    assert!(file_at_revision(fixture.path(), &oid, "missing.rs").is_none());
+    assert!(file_at_revision(fixture.path(), "0000000", "src/lib.rs").is_none());
+}
+
+#[test]
+fn worktree_root_finds_top_level_from_subdirectory() {
+    let fixture = GitRepoFixture::on_branch("main")
+        .and_then(|f| f.with_committed_file("nested/dir/file.txt", "x\n"))
+        .expect("build committed fixture");
+    let root = worktree_root_impl(Some(&fixture.path().join("nested/dir"))).expect("root");
+    assert_eq!(
+        root.canonicalize().expect("canonical root"),
+        fixture.path().canonicalize().expect("canonical fixture")
+    );
+}
+
+#[test]
+fn worktree_prefix_locates_subdirectory() {
+    let fixture = GitRepoFixture::on_branch("main")
+        .and_then(|f| f.with_committed_file("nested/dir/file.txt", "x\n"))
+        .expect("build committed fixture");
+    let nested = worktree_prefix_impl(Some(&fixture.path().join("nested/dir")));
+    assert_eq!(nested.as_deref(), Some("nested/dir/"));
+    assert_eq!(
+        worktree_prefix_impl(Some(fixture.path())).as_deref(),
+        Some("")
+    );
+}
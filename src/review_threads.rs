@@ -8,11 +8,15 @@
 //! CLI flag `--show-outdated`. Utilities for filtering threads by file
 //! path are also provided.
 
-use serde::Deserialize;
-use serde_json::{Map, json};
-use std::{borrow::Cow, collections::HashSet};
+use crate::head_context::FileExcerpt;
+use crate::worktree::{LocalLine, LocalStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{Map, Value, json};
+use std::{borrow::Cow, collections::VecDeque, ops::ControlFlow};
 
 use crate::boxed::BoxedStr;
+use crate::file_filter::FileFilter;
 use crate::graphql_queries::{COMMENT_QUERY, THREADS_QUERY};
 use crate::ref_parser::RepoInfo;
 use crate::{GraphQLClient, VkError};
@@ -55,18 +59,57 @@ type ReviewThreadConnection = Connection<ReviewThread>;
 pub type CommentConnection = Connection<ReviewComment>;
 
 /// Details of a single review thread.
-#[derive(Debug, Deserialize, Default)]
+///
+/// Threads serialise with their comments flattened to a plain list because
+/// pagination is exhausted before a thread leaves this module.
+#[derive(Debug, Deserialize, Serialize, Default)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "the flags mirror independent fields of GitHub's GraphQL schema"
+)]
 pub struct ReviewThread {
     pub id: String,
     #[serde(rename = "isResolved")]
     pub is_resolved: bool,
     #[serde(default, rename = "isOutdated")]
     pub is_outdated: bool,
+    /// Who resolved the thread; absent for unresolved threads.
+    #[serde(
+        default,
+        rename = "resolvedBy",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub resolved_by: Option<User>,
+    /// Whether the authenticated user may resolve the thread.
+    #[serde(default, rename = "viewerCanResolve")]
+    pub viewer_can_resolve: bool,
+    /// Whether the authenticated user may reply to the thread.
+    #[serde(default, rename = "viewerCanReply")]
+    pub viewer_can_reply: bool,
+    #[serde(serialize_with = "serialize_nodes")]
     pub comments: CommentConnection,
+    /// Whether the commented code changed in the local working tree, set
+    /// when `--local-status` is used.
+    #[serde(
+        default,
+        skip_deserializing,
+        rename = "localStatus",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub local_status: Option<LocalStatus>,
+}
+
+/// Serialise a fully paginated connection as the list of its nodes.
+fn serialize_nodes<S, T>(connection: &Connection<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    connection.nodes.serialize(serializer)
 }
 
 /// A single review comment.
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Deserialize, Serialize, Default)]
 pub struct ReviewComment {
     pub body: String,
     #[serde(rename = "diffHunk")]
@@ -74,9 +117,79 @@ pub struct ReviewComment {
     #[serde(rename = "originalPosition")]
     pub original_position: Option<i32>,
     pub position: Option<i32>,
+    /// First line of a multi-line comment; absent for single-line comments.
+    #[serde(default, rename = "startLine", skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
     pub path: String,
     pub url: String,
     pub author: Option<User>,
+    /// GitHub's numeric ID, as used in `#discussion_r<ID>` permalinks.
+    #[serde(
+        default,
+        rename = "databaseId",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub database_id: Option<u64>,
+    /// When the comment was posted.
+    #[serde(default, rename = "createdAt", skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the comment last changed, including edits by its author.
+    #[serde(default, rename = "updatedAt", skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// When the body was last edited; absent for comments never edited.
+    #[serde(
+        default,
+        rename = "lastEditedAt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_edited_at: Option<DateTime<Utc>>,
+    /// Reactions to the comment, one entry per emoji.
+    #[serde(
+        default,
+        rename(serialize = "reactions", deserialize = "reactionGroups"),
+        serialize_with = "serialize_reactions",
+        skip_serializing_if = "has_no_reactions"
+    )]
+    pub reactions: Vec<ReactionGroup>,
+    /// Lines around the comment in the file at the pull request head, set
+    /// when `--expand-context` is used.
+    #[serde(
+        default,
+        skip_deserializing,
+        rename = "headContext",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub head_context: Option<FileExcerpt>,
+    /// Where the commented line sits in the local working tree, set when
+    /// `--map-local` is used.
+    #[serde(
+        default,
+        skip_deserializing,
+        rename = "localLine",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub local_line: Option<LocalLine>,
+}
+
+/// Serialise reaction groups as `{content, count}` pairs, leaving out the
+/// emoji nobody used.
+fn serialize_reactions<S: Serializer>(
+    groups: &[ReactionGroup],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        groups
+            .iter()
+            .filter(|g| g.count() > 0)
+            .map(|g| ReactionCount {
+                content: g.content,
+                count: g.count(),
+            }),
+    )
+}
+
+fn has_no_reactions(groups: &[ReactionGroup]) -> bool {
+    groups.iter().all(|g| g.count() == 0)
 }
 
 /// Pagination information returned by GitHub's GraphQL API.
@@ -130,11 +243,83 @@ impl PageInfo {
 }
 
 /// Minimal user representation for authorship information.
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
 pub struct User {
     pub login: String,
 }
 
+/// Reactions of one kind to a comment.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct ReactionGroup {
+    pub content: ReactionContent,
+    pub reactors: ReactorCount,
+}
+
+impl ReactionGroup {
+    /// Number of people who reacted.
+    #[must_use]
+    pub const fn count(&self) -> u64 {
+        self.reactors.total_count
+    }
+}
+
+/// Reactions of one kind as written to JSON output.
+#[derive(Serialize)]
+struct ReactionCount {
+    content: ReactionContent,
+    count: u64,
+}
+
+/// Size of a reaction group.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ReactorCount {
+    #[serde(rename = "totalCount")]
+    pub total_count: u64,
+}
+
+/// The emoji GitHub offers as reactions.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReactionContent {
+    ThumbsUp,
+    ThumbsDown,
+    Laugh,
+    Hooray,
+    Confused,
+    Heart,
+    Rocket,
+    Eyes,
+    /// A reaction added to GitHub after this client was written.
+    #[serde(other)]
+    Other,
+}
+
+impl ReactionContent {
+    /// Emoji for the reaction, or its GitHub shortcode when `ascii` is true.
+    #[must_use]
+    pub const fn glyph(self, ascii: bool) -> &'static str {
+        match (self, ascii) {
+            (Self::ThumbsUp, false) => "\u{1f44d}",
+            (Self::ThumbsUp, true) => ":+1:",
+            (Self::ThumbsDown, false) => "\u{1f44e}",
+            (Self::ThumbsDown, true) => ":-1:",
+            (Self::Laugh, false) => "\u{1f604}",
+            (Self::Laugh, true) => ":smile:",
+            (Self::Hooray, false) => "\u{1f389}",
+            (Self::Hooray, true) => ":tada:",
+            (Self::Confused, false) => "\u{1f615}",
+            (Self::Confused, true) => ":confused:",
+            (Self::Heart, false) => "\u{2764}\u{fe0f}",
+            (Self::Heart, true) => ":heart:",
+            (Self::Rocket, false) => "\u{1f680}",
+            (Self::Rocket, true) => ":rocket:",
+            (Self::Eyes, false) => "\u{1f440}",
+            (Self::Eyes, true) => ":eyes:",
+            (Self::Other, _) => "?",
+        }
+    }
+}
+
 /// Options controlling which review threads to include.
 ///
 /// `include_resolved` retains resolved discussions; `include_outdated` keeps
@@ -211,47 +396,164 @@ pub async fn fetch_review_threads_with_options(
     number: u64,
     options: FetchOptions,
 ) -> Result<Vec<ReviewThread>, VkError> {
-    debug_assert!(
-        i32::try_from(number).is_ok(),
-        "pull-request number {number} exceeds GraphQL Int (i32) range",
-    );
-    let number_i32 = i32::try_from(number).map_err(|_| VkError::InvalidNumber)?;
-
-    let mut vars = Map::new();
-    vars.insert("owner".into(), json!(repo.owner.clone()));
-    vars.insert("name".into(), json!(repo.name.clone()));
-    vars.insert("number".into(), json!(number_i32));
-
-    let threads = client
-        .paginate_all(THREADS_QUERY, vars, None, |data: ThreadData| {
-            let conn = data.repository.pull_request.review_threads;
-            Ok((conn.nodes, conn.page_info))
-        })
-        .await?;
+    let mut threads = Vec::new();
+    for_each_review_thread(client, repo, number, options, |thread| {
+        threads.push(thread);
+        ControlFlow::Continue(())
+    })
+    .await?;
+    Ok(threads)
+}
 
-    let mut threads = if options.include_resolved {
-        threads
-    } else {
-        filter_unresolved_threads(threads)
-    };
+/// Most `reviewThreads` pages requested before giving up, guarding against
+/// cursors that never advance.
+const MAX_THREAD_PAGES: usize = 1000;
 
-    if !options.include_outdated {
-        threads = exclude_outdated_threads(threads);
+/// Review threads fetched one page of the `reviewThreads` connection at a
+/// time.
+///
+/// Each thread's comments are paginated when it is taken from the stream,
+/// and the next page of threads is only requested once the current one is
+/// used up, so consumers can act on early threads while later ones are
+/// still on the server.
+pub struct ReviewThreadStream<'a> {
+    client: &'a GraphQLClient,
+    vars: Map<String, Value>,
+    options: FetchOptions,
+    pending: VecDeque<ReviewThread>,
+    cursor: Option<String>,
+    pages: usize,
+    exhausted: bool,
+}
+
+impl<'a> ReviewThreadStream<'a> {
+    /// Stream the threads of pull request `number` in `repo` that pass
+    /// `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VkError::InvalidNumber`] if `number` exceeds `i32::MAX`.
+    pub fn new(
+        client: &'a GraphQLClient,
+        repo: &RepoInfo,
+        number: u64,
+        options: FetchOptions,
+    ) -> Result<Self, VkError> {
+        debug_assert!(
+            i32::try_from(number).is_ok(),
+            "pull-request number {number} exceeds GraphQL Int (i32) range",
+        );
+        let number_i32 = i32::try_from(number).map_err(|_| VkError::InvalidNumber)?;
+        let mut vars = Map::new();
+        vars.insert("owner".into(), json!(repo.owner.clone()));
+        vars.insert("name".into(), json!(repo.name.clone()));
+        vars.insert("number".into(), json!(number_i32));
+        Ok(Self {
+            client,
+            vars,
+            options,
+            pending: VecDeque::new(),
+            cursor: None,
+            pages: 0,
+            exhausted: false,
+        })
     }
 
-    for thread in &mut threads {
-        let initial = std::mem::take(&mut thread.comments);
-        thread.comments = fetch_all_comments(client, &thread.id, initial).await?;
-        for (idx, comment) in thread.comments.nodes.iter().enumerate() {
-            if comment.path.trim().is_empty() {
-                return Err(VkError::EmptyCommentPath {
-                    thread_id: thread.id.clone().into_boxed_str(),
-                    index: idx,
-                });
+    /// Take the next thread with all of its comments, or `None` once every
+    /// page has been read.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`VkError`] if any API request fails, the response is
+    /// malformed, or a comment has no path.
+    pub async fn next_thread(&mut self) -> Result<Option<ReviewThread>, VkError> {
+        loop {
+            if let Some(mut thread) = self.pending.pop_front() {
+                let initial = std::mem::take(&mut thread.comments);
+                thread.comments = fetch_all_comments(self.client, &thread.id, initial).await?;
+                for (idx, comment) in thread.comments.nodes.iter().enumerate() {
+                    if comment.path.trim().is_empty() {
+                        return Err(VkError::EmptyCommentPath {
+                            thread_id: thread.id.clone().into_boxed_str(),
+                            index: idx,
+                        });
+                    }
+                }
+                return Ok(Some(thread));
             }
+            if self.exhausted {
+                return Ok(None);
+            }
+            self.fetch_page().await?;
         }
     }
-    Ok(threads)
+
+    /// Request the next page of threads and queue those passing the
+    /// resolution and outdated filters.
+    async fn fetch_page(&mut self) -> Result<(), VkError> {
+        self.pages += 1;
+        if self.pages > MAX_THREAD_PAGES {
+            return Err(VkError::BadResponse(
+                format!("pagination exceeded max pages {MAX_THREAD_PAGES}").boxed(),
+            ));
+        }
+        let data: ThreadData = self
+            .client
+            .fetch_page(
+                THREADS_QUERY,
+                self.cursor.take().map(Cow::Owned),
+                &self.vars,
+            )
+            .await?;
+        let conn = data.repository.pull_request.review_threads;
+        match conn.page_info.next_cursor()? {
+            Some(next) => self.cursor = Some(next.to_owned()),
+            None => self.exhausted = true,
+        }
+        let mut threads = conn.nodes;
+        if !self.options.include_resolved {
+            threads = filter_unresolved_threads(threads);
+        }
+        if !self.options.include_outdated {
+            threads = exclude_outdated_threads(threads);
+        }
+        self.pending.extend(threads);
+        Ok(())
+    }
+}
+
+/// Fetch review threads and hand each one to `on_thread` as soon as its
+/// comment pagination completes.
+///
+/// Threads are delivered in the order returned by the `reviewThreads`
+/// connection, filtered by `options` exactly as in
+/// [`fetch_review_threads_with_options`]. Pages of threads are requested as
+/// they are needed, so the first thread arrives after one page rather than
+/// the whole list. Returning [`ControlFlow::Break`] from `on_thread` stops
+/// further requests, which lets streaming consumers give up once their
+/// output is closed.
+///
+/// # Errors
+///
+/// Returns [`VkError::InvalidNumber`] if `number` exceeds `i32::MAX`, or a
+/// general [`VkError`] if any API request fails or the response is malformed.
+pub async fn for_each_review_thread<F>(
+    client: &GraphQLClient,
+    repo: &RepoInfo,
+    number: u64,
+    options: FetchOptions,
+    mut on_thread: F,
+) -> Result<(), VkError>
+where
+    F: FnMut(ReviewThread) -> ControlFlow<()>,
+{
+    let mut stream = ReviewThreadStream::new(client, repo, number, options)?;
+    while let Some(thread) = stream.next_thread().await? {
+        if on_thread(thread).is_break() {
+            break;
+        }
+    }
+    Ok(())
 }
 
 /// Fetch all comments for a thread, following pagination when required.
@@ -343,13 +645,14 @@ pub fn exclude_outdated_threads(threads: Vec<ReviewThread>) -> Vec<ReviewThread>
 /// Alias retained for documentation and CLI consistency.
 pub use exclude_outdated_threads as filter_outdated_threads;
 
-/// Filter review threads to those whose first comment matches one of `files`.
+/// Filter review threads to those whose first comment's path passes `files`.
 ///
-/// Returns the original collection when `files` is empty.
+/// Returns the original collection when no file pattern was given.
 ///
 /// # Examples
 ///
 /// ```
+/// use vk::file_filter::FileFilter;
 /// use vk::review_threads::{
 ///     filter_threads_by_files, CommentConnection, ReviewComment, ReviewThread,
 /// };
@@ -369,7 +672,8 @@ pub use exclude_outdated_threads as filter_outdated_threads;
 ///         ..Default::default()
 ///     },
 /// ];
-/// let filtered = filter_threads_by_files(threads, &[String::from("README.md")]);
+/// let files = FileFilter::new(&[String::from("README.md")], "").expect("valid pattern");
+/// let filtered = filter_threads_by_files(threads, &files);
 /// assert_eq!(filtered.len(), 1);
 /// let path = filtered
 ///     .first()
@@ -377,18 +681,21 @@ pub use exclude_outdated_threads as filter_outdated_threads;
 ///     .map(|c| c.path.as_str());
 /// assert_eq!(path, Some("README.md"));
 /// ```
-pub fn filter_threads_by_files(threads: Vec<ReviewThread>, files: &[String]) -> Vec<ReviewThread> {
-    if files.is_empty() {
+#[must_use]
+pub fn filter_threads_by_files(
+    threads: Vec<ReviewThread>,
+    files: &FileFilter,
+) -> Vec<ReviewThread> {
+    if !files.is_active() {
         return threads;
     }
-    let set: HashSet<&str> = files.iter().map(String::as_str).collect();
     threads
         .into_iter()
         .filter(|t| {
             t.comments
                 .nodes
                 .first()
-                .is_some_and(|c| set.contains(c.path.as_str()))
+                .is_some_and(|c| files.matches(&c.path))
         })
         .collect()
 }
@@ -8,6 +8,7 @@ use crate::test_utils::{TestClient, start_server};
 #[cfg(debug_assertions)]
 use futures::FutureExt;
 use rstest::{fixture, rstest};
+use std::ops::ControlFlow;
 #[cfg(debug_assertions)]
 use std::panic::AssertUnwindSafe;
 use std::sync::atomic::Ordering;
@@ -313,7 +314,7 @@ fn filter_threads_by_files_retains_matches() {
             ..Default::default()
         },
     ];
-    let files = vec![String::from("README.md")];
+    let files = FileFilter::new(&[String::from("README.md")], "").expect("valid pattern");
     let filtered = filter_threads_by_files(threads, &files);
     assert_eq!(filtered.len(), 1);
     let path = filtered
@@ -653,3 +654,90 @@ async fn fetch_review_threads_with_options_filters_outdated(
     join.abort();
     let _ = join.await;
 }
+
+#[rstest]
+#[tokio::test]
+async fn for_each_review_thread_stops_when_consumer_breaks(repo: RepoInfo) {
+    let threads_body = serde_json::json!({
+        "data": {"repository": {"pullRequest": {"reviewThreads": {
+            "nodes": [
+                {
+                    "id": "t1",
+                    "isResolved": false,
+                    "isOutdated": false,
+                    "comments": {"nodes": [], "pageInfo": {"hasNextPage": true, "endCursor": "c1"}}
+                },
+                {
+                    "id": "t2",
+                    "isResolved": false,
+                    "isOutdated": false,
+                    "comments": {"nodes": [], "pageInfo": {"hasNextPage": true, "endCursor": "c2"}}
+                }
+            ],
+            "pageInfo": {"hasNextPage": false, "endCursor": null}
+        }}}}
+    })
+    .to_string();
+    let comment_body = serde_json::json!({
+        "data": {"node": {"comments": {
+            "nodes": [comment("c1")],
+            "pageInfo": {"hasNextPage": false, "endCursor": null}
+        }}}
+    })
+    .to_string();
+    let TestClient { client, join, hits } = start_server(vec![threads_body, comment_body]);
+    let mut seen = Vec::new();
+    for_each_review_thread(
+        &client,
+        &repo,
+        1,
+        FetchOptions::unresolved_current(),
+        |thread| {
+            seen.push(thread.id);
+            ControlFlow::Break(())
+        },
+    )
+    .await
+    .expect("stream threads");
+    assert_eq!(seen, ["t1"]);
+    // The second thread's comments are never requested once the consumer
+    // stops.
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    join.abort();
+    let _ = join.await;
+}
+
+#[rstest]
+#[tokio::test]
+async fn thread_pages_are_fetched_as_needed(repo: RepoInfo) {
+    let page = |id: &str, next: Option<&str>| {
+        serde_json::json!({
+            "data": {"repository": {"pullRequest": {"reviewThreads": {
+                "nodes": [{
+                    "id": id,
+                    "isResolved": false,
+                    "isOutdated": false,
+                    "comments": {
+                        "nodes": [comment(id)],
+                        "pageInfo": {"hasNextPage": false, "endCursor": null}
+                    }
+                }],
+                "pageInfo": {"hasNextPage": next.is_some(), "endCursor": next}
+            }}}}
+        })
+        .to_string()
+    };
+    let TestClient { client, join, hits } =
+        start_server(vec![page("t1", Some("p2")), page("t2", None)]);
+    let mut stream = ReviewThreadStream::new(&client, &repo, 1, FetchOptions::unresolved_current())
+        .expect("stream");
+    let first = stream.next_thread().await.expect("first thread");
+    assert_eq!(first.map(|t| t.id), Some("t1".into()));
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    let second = stream.next_thread().await.expect("second thread");
+    assert_eq!(second.map(|t| t.id), Some("t2".into()));
+    assert!(stream.next_thread().await.expect("end").is_none());
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    join.abort();
+    let _ = join.await;
+}
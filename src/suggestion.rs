@@ -0,0 +1,367 @@
+//! GitHub suggested changes: rendering and applying them locally.
+//!
+//! A reviewer's ` ```suggestion ` block holds the text that should replace
+//! the commented lines. The printers turn each block into a `diff` block
+//! against those lines, and `vk apply` writes the replacement into the local
+//! file after checking the lines still match the diff hunk.
+
+use std::borrow::Cow;
+
+use crate::ReviewComment;
+use crate::diff::{comment_line, hunk_lines};
+
+/// Part of a comment body: an ordinary line or a suggestion block.
+enum Segment<'a> {
+    Text(&'a str),
+    Suggestion { fence: &'a str, lines: Vec<&'a str> },
+}
+
+/// Return the fence opening a suggestion block on `line`, if any.
+fn suggestion_fence(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.chars().take_while(|c| *c == marker).count();
+    if len < 3 {
+        return None;
+    }
+    let (fence, info) = trimmed.split_at(len);
+    (info.trim() == "suggestion").then_some(fence)
+}
+
+/// Split `body` into text lines and closed suggestion blocks.
+///
+/// An unclosed block is kept as text, matching how GitHub refuses to offer
+/// it as a suggestion.
+fn segments(body: &str) -> Vec<Segment<'_>> {
+    let lines: Vec<&str> = body.lines().map(|l| l.trim_end_matches('\r')).collect();
+    let mut out = Vec::new();
+    let mut idx = 0;
+    while let Some(&line) = lines.get(idx) {
+        idx += 1;
+        let Some(fence) = suggestion_fence(line) else {
+            out.push(Segment::Text(line));
+            continue;
+        };
+        let rest = lines.get(idx..).unwrap_or_default();
+        let Some(close) = rest.iter().position(|l| {
+            let l = l.trim();
+            l.len() >= fence.len() && l.chars().all(|c| fence.starts_with(c))
+        }) else {
+            out.push(Segment::Text(line));
+            continue;
+        };
+        out.push(Segment::Suggestion {
+            fence,
+            lines: rest.get(..close).unwrap_or_default().to_vec(),
+        });
+        idx += close + 1;
+    }
+    out
+}
+
+/// Return the replacement lines of each suggestion block in `body`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use vk::suggestion::suggestions;
+/// let body = "Try this:\n```suggestion\nlet x = 1;\n```\n";
+/// assert_eq!(suggestions(body), [vec!["let x = 1;"]]);
+/// ```
+#[must_use]
+pub fn suggestions(body: &str) -> Vec<Vec<&str>> {
+    segments(body)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Suggestion { lines, .. } => Some(lines),
+            Segment::Text(_) => None,
+        })
+        .collect()
+}
+
+/// The line range a comment's suggestion replaces, with the lines the diff
+/// hunk shows there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentedLines {
+    /// First replaced line (1-based).
+    pub start: u32,
+    /// Last replaced line (inclusive).
+    pub end: u32,
+    /// Text of the replaced lines at the commented commit.
+    pub lines: Vec<String>,
+}
+
+impl CommentedLines {
+    /// Locate the lines `comment` covers, or `None` when its hunk does not
+    /// show them.
+    #[must_use]
+    pub fn of(comment: &ReviewComment) -> Option<Self> {
+        let end = comment_line(comment)?;
+        let start = comment.start_line.filter(|s| *s <= end).unwrap_or(end);
+        let lines = hunk_lines(comment, start, end)?;
+        Some(Self { start, end, lines })
+    }
+}
+
+/// Rewrite each suggestion block in the comment body as a `diff` block that
+/// removes the commented lines and adds the suggested ones.
+///
+/// Bodies without suggestions are borrowed unchanged. When the commented
+/// lines cannot be recovered from the hunk, the diff shows only additions.
+///
+/// # Examples
+///
+/// ```ignore
+/// use vk::suggestion::render_suggestions;
+/// # use vk::ReviewComment;
+/// let comment = ReviewComment {
+///     body: "```suggestion\nnew\n```".into(),
+///     diff_hunk: "@@ -1 +1 @@\n+old".into(),
+///     ..Default::default()
+/// };
+/// assert_eq!(render_suggestions(&comment), "```diff\n-old\n+new\n```\n");
+/// ```
+#[must_use]
+pub fn render_suggestions(comment: &ReviewComment) -> Cow<'_, str> {
+    render_suggestions_in(&comment.body, comment)
+}
+
+/// Rewrite suggestion blocks in `body`, which may differ from the comment's
+/// own body, against the lines `comment` covers.
+#[must_use]
+pub fn render_suggestions_in<'a>(body: &'a str, comment: &ReviewComment) -> Cow<'a, str> {
+    let parts = segments(body);
+    if !parts
+        .iter()
+        .any(|s| matches!(s, Segment::Suggestion { .. }))
+    {
+        return Cow::Borrowed(body);
+    }
+    let original = CommentedLines::of(comment)
+        .map(|c| c.lines)
+        .unwrap_or_default();
+    let mut out = String::with_capacity(body.len() * 2);
+    for part in parts {
+        match part {
+            Segment::Text(line) => {
+                out.push_str(line);
+                out.push('\n');
+            }
+            Segment::Suggestion { fence, lines } => {
+                out.push_str(fence);
+                out.push_str("diff\n");
+                for (prefix, line) in original
+                    .iter()
+                    .map(|l| ('-', l.as_str()))
+                    .chain(lines.iter().map(|l| ('+', *l)))
+                {
+                    out.push(prefix);
+                    out.push_str(line);
+                    out.push('\n');
+                }
+                out.push_str(fence);
+                out.push('\n');
+            }
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Why a suggestion could not be applied to a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// The commented lines were not found in the file.
+    Missing,
+    /// The commented lines occur more than once away from their original
+    /// position, so the target is ambiguous.
+    Ambiguous,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Missing => "the commented lines differ from the diff hunk",
+            Self::Ambiguous => "the commented lines appear more than once",
+        })
+    }
+}
+
+/// Replace the commented lines in `text` with `replacement`.
+///
+/// The lines are expected at `commented.start`; when the file has shifted
+/// they are accepted anywhere else provided they occur exactly once. Lines
+/// outside the suggestion keep their own endings, replacement lines take the
+/// ending of the lines they replace, and a missing final newline is
+/// preserved.
+///
+/// # Errors
+///
+/// Returns a [`Conflict`] when the commented lines cannot be located.
+pub fn apply_suggestion(
+    text: &str,
+    commented: &CommentedLines,
+    replacement: &[&str],
+) -> Result<String, Conflict> {
+    let lines: Vec<(&str, &str)> = text.split_inclusive('\n').map(split_eol).collect();
+    let file: Vec<&str> = lines.iter().map(|&(line, _)| line).collect();
+    let wanted: Vec<&str> = commented.lines.iter().map(String::as_str).collect();
+    let matches_at = |idx: usize| {
+        file.get(idx..idx + wanted.len())
+            .is_some_and(|window| window == wanted.as_slice())
+    };
+    let expected =
+        usize::try_from(commented.start.saturating_sub(1)).map_err(|_| Conflict::Missing)?;
+    let idx = if matches_at(expected) {
+        expected
+    } else {
+        let mut found = (0..file.len()).filter(|&idx| matches_at(idx));
+        match (found.next(), found.next()) {
+            (Some(idx), None) => idx,
+            (None, _) => return Err(Conflict::Missing),
+            (Some(_), Some(_)) => return Err(Conflict::Ambiguous),
+        }
+    };
+    let before = lines.get(..idx).unwrap_or_default();
+    let replaced = lines.get(idx..idx + wanted.len()).unwrap_or_default();
+    let after = lines.get(idx + wanted.len()..).unwrap_or_default();
+    let endings = |lines: &[(&str, &'static str)]| {
+        lines
+            .iter()
+            .map(|&(_, eol)| eol)
+            .find(|eol| !eol.is_empty())
+    };
+    let eol = endings(replaced)
+        .or_else(|| endings(&lines))
+        .unwrap_or("\n");
+    let last = replaced.last().map_or(eol, |&(_, eol)| eol);
+    let count = replacement.len();
+    let mut out: String = before
+        .iter()
+        .copied()
+        .chain(
+            replacement
+                .iter()
+                .enumerate()
+                .map(|(i, &line)| (line, if i + 1 == count { last } else { eol })),
+        )
+        .chain(after.iter().copied())
+        .flat_map(|(line, eol)| [line, eol])
+        .collect();
+    // Deleting an unterminated final line leaves the new last line
+    // unterminated too.
+    if count == 0 && after.is_empty() && last.is_empty() {
+        let kept = out
+            .strip_suffix('\n')
+            .map(|s| s.strip_suffix('\r').unwrap_or(s));
+        if let Some(kept) = kept {
+            out.truncate(kept.len());
+        }
+    }
+    Ok(out)
+}
+
+/// Split a line from [`str::split_inclusive`] into its content and ending.
+fn split_eol(line: &str) -> (&str, &'static str) {
+    ["\r\n", "\n"]
+        .into_iter()
+        .find_map(|eol| line.strip_suffix(eol).map(|content| (content, eol)))
+        .unwrap_or((line, ""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn comment(body: &str, hunk: &str) -> ReviewComment {
+        ReviewComment {
+            body: body.into(),
+            diff_hunk: hunk.into(),
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case::simple("```suggestion\nnew\n```", vec![vec!["new"]])]
+    #[case::deletion("```suggestion\n```", vec![vec![]])]
+    #[case::longer_fence("````suggestion\n```\n````", vec![vec!["```"]])]
+    #[case::tilde_crlf("~~~ suggestion\r\na\r\nb\r\n~~~\r\n", vec![vec!["a", "b"]])]
+    #[case::plain_code("```rust\nx\n```", vec![])]
+    #[case::unclosed("```suggestion\nx", vec![])]
+    fn suggestions_are_parsed(#[case] body: &str, #[case] expected: Vec<Vec<&str>>) {
+        assert_eq!(suggestions(body), expected);
+    }
+
+    #[test]
+    fn multi_line_comments_use_start_line() {
+        let mut c = comment("", "@@ -1,3 +1,3 @@\n a\n b\n c");
+        c.position = Some(3);
+        c.start_line = Some(2);
+        assert_eq!(
+            CommentedLines::of(&c),
+            Some(CommentedLines {
+                start: 2,
+                end: 3,
+                lines: vec!["b".into(), "c".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn suggestion_renders_as_diff() {
+        let c = comment(
+            "Prefer this:\n```suggestion\nlet y = 2;\n```\nThanks",
+            "@@ -1,2 +1,2 @@\n fn f() {\n-let x = 1;\n+let y = 1;",
+        );
+        assert_eq!(
+            render_suggestions(&c),
+            "Prefer this:\n```diff\n-let y = 1;\n+let y = 2;\n```\nThanks\n"
+        );
+    }
+
+    #[test]
+    fn bodies_without_suggestions_are_borrowed() {
+        let c = comment("```rust\nlet x = 1;\n```", "@@ -1 +1 @@\n+x");
+        assert!(matches!(render_suggestions(&c), Cow::Borrowed(_)));
+    }
+
+    fn commented(start: u32, lines: &[&str]) -> CommentedLines {
+        CommentedLines {
+            start,
+            end: start + u32::try_from(lines.len()).expect("small") - 1,
+            lines: lines.iter().map(|&l| l.to_owned()).collect(),
+        }
+    }
+
+    #[rstest]
+    #[case::in_place("a\nb\nc\n", 2, Ok("a\nB\nc\n"))]
+    #[case::shifted("new\na\nb\nc\n", 2, Ok("new\na\nB\nc\n"))]
+    #[case::crlf_no_final_newline("a\r\nb\r\nc", 2, Ok("a\r\nB\r\nc"))]
+    #[case::last_line_no_final_newline("a\nb", 2, Ok("a\nB"))]
+    #[case::mixed_endings("a\r\nb\nc\r\n", 2, Ok("a\r\nB\nc\r\n"))]
+    #[case::edited("a\nbee\nc\n", 2, Err(Conflict::Missing))]
+    #[case::ambiguous("b\nb\nc\n", 3, Err(Conflict::Ambiguous))]
+    fn suggestion_applies_or_conflicts(
+        #[case] text: &str,
+        #[case] start: u32,
+        #[case] expected: Result<&str, Conflict>,
+    ) {
+        let result = apply_suggestion(text, &commented(start, &["b"]), &["B"]);
+        assert_eq!(result, expected.map(str::to_owned));
+    }
+
+    #[rstest]
+    #[case::whole_file("a", &["a"], "")]
+    #[case::whole_file_with_newline("a\n", &["a"], "")]
+    #[case::unterminated_last_line("a\nb", &["b"], "a")]
+    #[case::crlf_middle_line("a\r\nb\r\nc\r\n", &["b"], "a\r\nc\r\n")]
+    fn deletion_keeps_line_endings(
+        #[case] text: &str,
+        #[case] lines: &[&str],
+        #[case] expected: &str,
+    ) {
+        let start = if lines == ["b"] { 2 } else { 1 };
+        let result = apply_suggestion(text, &commented(start, lines), &[]);
+        assert_eq!(result.as_deref(), Ok(expected));
+    }
+}
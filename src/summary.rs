@@ -27,17 +27,58 @@ use vk::banners::{COMMENTS_BANNER, END_BANNER, START_BANNER};
 /// ```
 #[must_use]
 pub fn summarize_files(threads: &[ReviewThread]) -> Vec<(String, usize)> {
-    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut tally = FileTally::default();
     for t in threads {
-        for c in &t.comments.nodes {
-            *counts.entry(c.path.clone()).or_default() += 1;
+        tally.add_thread(t);
+    }
+    tally.into_summary()
+}
+
+/// Produce a count of comments per file path, listing files in the order
+/// they first appear in `threads` rather than by count.
+///
+/// Used when threads are sorted or grouped, so the summary matches the body.
+#[must_use]
+pub fn summarize_files_in_order<'a>(
+    threads: impl IntoIterator<Item = &'a ReviewThread>,
+) -> Vec<(String, usize)> {
+    let mut summary: Vec<(String, usize)> = Vec::new();
+    for c in threads.into_iter().flat_map(|t| &t.comments.nodes) {
+        if let Some((_, count)) = summary.iter_mut().find(|(path, _)| *path == c.path) {
+            *count += 1;
+        } else {
+            summary.push((c.path.clone(), 1));
         }
     }
-    let mut v: Vec<_> = counts.into_iter().collect();
-    // Sort by descending count to surface files with the most discussion.
-    // Break ties alphabetically for stable output.
-    v.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
-    v
+    summary
+}
+
+/// Running count of comments per file path.
+///
+/// Streaming renderers feed threads in one at a time and produce the same
+/// summary as [`summarize_files`] once every thread has been seen.
+#[derive(Debug, Default)]
+pub struct FileTally {
+    counts: BTreeMap<String, usize>,
+}
+
+impl FileTally {
+    /// Count every comment in `thread` against its file path.
+    pub fn add_thread(&mut self, thread: &ReviewThread) {
+        for c in &thread.comments.nodes {
+            *self.counts.entry(c.path.clone()).or_default() += 1;
+        }
+    }
+
+    /// Finish counting and return the summary in display order.
+    #[must_use]
+    pub fn into_summary(self) -> Vec<(String, usize)> {
+        let mut v: Vec<_> = self.counts.into_iter().collect();
+        // Sort by descending count to surface files with the most discussion.
+        // Break ties alphabetically for stable output.
+        v.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        v
+    }
 }
 
 /// Write a preformatted summary to any writer.
@@ -290,6 +331,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn file_tally_matches_summarize_files() {
+        let threads = vec![
+            ReviewThread {
+                comments: CommentConnection {
+                    nodes: vec![review_comment("b.rs"), review_comment("a.rs")],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ReviewThread {
+                comments: CommentConnection {
+                    nodes: vec![review_comment("b.rs")],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ];
+        let mut tally = FileTally::default();
+        for thread in &threads {
+            tally.add_thread(thread);
+        }
+        assert_eq!(tally.into_summary(), summarize_files(&threads));
+    }
+
+    #[test]
+    fn summarize_files_in_order_follows_threads() {
+        let threads = vec![
+            ReviewThread {
+                comments: CommentConnection {
+                    nodes: vec![review_comment("b.rs")],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ReviewThread {
+                comments: CommentConnection {
+                    nodes: vec![review_comment("a.rs"), review_comment("a.rs")],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            summarize_files_in_order(&threads),
+            vec![("b.rs".into(), 1), ("a.rs".into(), 2)]
+        );
+    }
+
     #[test]
     fn write_summary_outputs_text() {
         let summary = vec![("foo.rs".into(), 1)];
@@ -122,6 +122,51 @@ impl GitRepoFixture {
         Ok(self)
     }
 
+    /// Write `content` to `path` and commit it.
+    ///
+    /// Sets a local identity and disables signing so the commit works in
+    /// hermetic environments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `io::Error` when the file cannot be written or any `git`
+    /// invocation fails.
+    pub fn with_committed_file(self, path: &str, content: &str) -> io::Result<Self> {
+        let dir = self.dir.path();
+        let full = dir.join(path);
+        if let Some(parent) = full.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(full, content)?;
+        for (key, value) in [("user.email", "test@test.com"), ("user.name", "Test")] {
+            run_git_in(dir, &["config", key, value])?;
+        }
+        run_git_in(dir, &["add", path])?;
+        run_git_in(
+            dir,
+            &["-c", "commit.gpgsign=false", "commit", "-m", "add file"],
+        )?;
+        Ok(self)
+    }
+
+    /// Return the object name of the commit at `HEAD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `io::Error` when `git rev-parse` cannot be spawned or fails.
+    pub fn head_oid(&self) -> io::Result<String> {
+        let output = std::process::Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(self.dir.path())
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other("git rev-parse HEAD failed"));
+        }
+        String::from_utf8(output.stdout)
+            .map(|oid| oid.trim().to_owned())
+            .map_err(io::Error::other)
+    }
+
     /// Path to the repository's working directory.
     #[must_use]
     pub fn path(&self) -> &Path {
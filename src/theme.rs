@@ -0,0 +1,341 @@
+//! Colour themes for terminal output.
+//!
+//! A theme decides how Termimad styles comment bodies and how diff hunks and
+//! author banners are coloured. Built-in themes are picked with `--theme` or
+//! the `theme` configuration key, and the `[colours]` table overrides single
+//! colours on top of them. Setting `NO_COLOR` selects the `no-colour` theme
+//! whatever else is configured.
+
+use termimad::crossterm::style::{Attribute, Color};
+use termimad::{CompoundStyle, MadSkin};
+
+use crate::cli_args::{Colour, GlobalArgs, ThemeColours, ThemeName};
+use crate::environment;
+use vk::icons::Icons;
+
+/// Colours for diff hunks and author banners.
+///
+/// Kept separate from the Termimad skin because these parts of the output
+/// are written by `vk` itself rather than rendered from Markdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Colour of the `+` marker on added lines.
+    pub added: Option<Color>,
+    /// Colour of the `-` marker on removed lines.
+    pub removed: Option<Color>,
+    /// Background of added lines.
+    pub added_bg: Option<Color>,
+    /// Background of removed lines.
+    pub removed_bg: Option<Color>,
+    /// Bundled `syntect` theme used to colour code, if any.
+    pub syntax_theme: Option<&'static str>,
+    /// Colour of author names in banners.
+    pub author: Option<Color>,
+}
+
+impl Palette {
+    const DARK: Self = Self {
+        added: Some(Color::DarkGreen),
+        removed: Some(Color::DarkRed),
+        added_bg: Some(Color::Rgb {
+            r: 16,
+            g: 56,
+            b: 24,
+        }),
+        removed_bg: Some(Color::Rgb {
+            r: 72,
+            g: 20,
+            b: 24,
+        }),
+        syntax_theme: Some("base16-ocean.dark"),
+        author: None,
+    };
+
+    const LIGHT: Self = Self {
+        added: Some(Color::DarkGreen),
+        removed: Some(Color::DarkRed),
+        added_bg: Some(Color::Rgb {
+            r: 218,
+            g: 251,
+            b: 225,
+        }),
+        removed_bg: Some(Color::Rgb {
+            r: 255,
+            g: 235,
+            b: 233,
+        }),
+        syntax_theme: Some("InspiredGitHub"),
+        author: Some(Color::DarkBlue),
+    };
+
+    const HIGH_CONTRAST: Self = Self {
+        added: Some(Color::Green),
+        removed: Some(Color::Red),
+        added_bg: None,
+        removed_bg: None,
+        syntax_theme: None,
+        author: Some(Color::Yellow),
+    };
+
+    const NO_COLOUR: Self = Self {
+        added: None,
+        removed: None,
+        added_bg: None,
+        removed_bg: None,
+        syntax_theme: None,
+        author: None,
+    };
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+/// A resolved theme: a built-in base plus configured overrides.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Theme {
+    name: ThemeName,
+    colours: ThemeColours,
+}
+
+impl Theme {
+    /// Build `name` with `colours` layered on top.
+    #[must_use]
+    pub fn new(name: ThemeName, colours: ThemeColours) -> Self {
+        Self { name, colours }
+    }
+
+    /// Resolve the theme from global options, honouring `NO_COLOR`.
+    #[must_use]
+    pub fn from_args(global: &GlobalArgs) -> Self {
+        let name = if no_color_requested() {
+            ThemeName::NoColour
+        } else {
+            global.theme.unwrap_or_default()
+        };
+        Self::new(name, global.colours.unwrap_or_default())
+    }
+
+    /// Whether diff hunks should be coloured at all.
+    #[must_use]
+    pub fn colours_diffs(&self) -> bool {
+        self.name != ThemeName::NoColour
+    }
+
+    /// Colours for diff hunks and author banners.
+    #[must_use]
+    pub fn palette(&self) -> Palette {
+        let mut palette = match self.name {
+            ThemeName::Dark => Palette::DARK,
+            ThemeName::Light => Palette::LIGHT,
+            ThemeName::HighContrast => Palette::HIGH_CONTRAST,
+            ThemeName::NoColour => return Palette::NO_COLOUR,
+        };
+        let ThemeColours {
+            added,
+            removed,
+            author,
+            ..
+        } = self.colours;
+        palette.added_bg = added.map(|c| c.0).or(palette.added_bg);
+        palette.removed_bg = removed.map(|c| c.0).or(palette.removed_bg);
+        palette.author = author.map(|c| c.0).or(palette.author);
+        palette
+    }
+
+    /// Build the Termimad skin for comment bodies.
+    ///
+    /// With [`Icons::ASCII`] the skin draws bullets, quotes, rules and table
+    /// borders with ASCII characters instead of Unicode symbols and
+    /// box-drawing characters.
+    #[must_use]
+    pub fn skin(&self, icons: Icons) -> MadSkin {
+        let mut skin = match self.name {
+            ThemeName::Dark => MadSkin::default(),
+            ThemeName::Light => MadSkin::default_light(),
+            ThemeName::HighContrast => high_contrast_skin(),
+            ThemeName::NoColour => no_colour_skin(),
+        };
+        if self.name != ThemeName::NoColour {
+            let ThemeColours {
+                header,
+                code,
+                inline_code,
+                ..
+            } = self.colours;
+            if let Some(Colour(c)) = header {
+                skin.set_headers_fg(c);
+            }
+            if let Some(Colour(c)) = code {
+                skin.code_block.compound_style.set_fg(c);
+            }
+            if let Some(Colour(c)) = inline_code {
+                skin.inline_code.set_fg(c);
+            }
+        }
+        if icons.is_ascii() {
+            skin.limit_to_ascii();
+        }
+        skin
+    }
+}
+
+/// Whether `NO_COLOR` is set to a non-empty value.
+fn no_color_requested() -> bool {
+    environment::var("NO_COLOR").is_ok_and(|v| !v.is_empty())
+}
+
+/// A skin with text attributes but no colours.
+fn no_colour_skin() -> MadSkin {
+    let mut skin = MadSkin::no_style();
+    skin.bold = CompoundStyle::with_attr(Attribute::Bold);
+    skin.italic = CompoundStyle::with_attr(Attribute::Italic);
+    skin.strikeout = CompoundStyle::with_attr(Attribute::CrossedOut);
+    for header in &mut skin.headers {
+        header.add_attr(Attribute::Underlined);
+    }
+    if let Some(title) = skin.headers.first_mut() {
+        title.add_attr(Attribute::Bold);
+    }
+    skin
+}
+
+/// A skin using bright basic colours and no background tints.
+fn high_contrast_skin() -> MadSkin {
+    let mut skin = no_colour_skin();
+    for header in &mut skin.headers {
+        header.set_fg(Color::Yellow);
+        header.add_attr(Attribute::Bold);
+    }
+    skin.inline_code = CompoundStyle::with_fg(Color::Cyan);
+    skin.code_block.compound_style = CompoundStyle::with_fg(Color::Cyan);
+    skin.bullet.set_fg(Color::White);
+    skin.quote_mark.set_fg(Color::White);
+    skin.horizontal_rule.set_fg(Color::White);
+    skin
+}
+
+/// Select Graphic Rendition parameters for `color` as a foreground, or as a
+/// background when `background` is true.
+///
+/// Named colours use the basic 16-colour codes understood by every
+/// terminal; palette indices and RGB triples use the extended forms.
+#[must_use]
+pub fn sgr(color: Color, background: bool) -> String {
+    let base = if background { 40 } else { 30 };
+    let basic = |offset: u8| (base + offset).to_string();
+    let bright = |offset: u8| (base + 60 + offset).to_string();
+    let extended = if background { "48" } else { "38" };
+    match color {
+        Color::Reset => (base + 9).to_string(),
+        Color::Black => basic(0),
+        Color::DarkRed => basic(1),
+        Color::DarkGreen => basic(2),
+        Color::DarkYellow => basic(3),
+        Color::DarkBlue => basic(4),
+        Color::DarkMagenta => basic(5),
+        Color::DarkCyan => basic(6),
+        Color::Grey => basic(7),
+        Color::DarkGrey => bright(0),
+        Color::Red => bright(1),
+        Color::Green => bright(2),
+        Color::Yellow => bright(3),
+        Color::Blue => bright(4),
+        Color::Magenta => bright(5),
+        Color::Cyan => bright(6),
+        Color::White => bright(7),
+        Color::AnsiValue(index) => format!("{extended};5;{index}"),
+        Color::Rgb { r, g, b } => format!("{extended};2;{r};{g};{b}"),
+    }
+}
+
+/// Escape sequence setting `color` as a foreground, or an empty string when
+/// there is no colour.
+#[must_use]
+pub fn fg_escape(color: Option<Color>) -> String {
+    color.map_or_else(String::new, |c| format!("\x1b[{}m", sgr(c, false)))
+}
+
+/// Escape sequence setting `color` as a background, or an empty string when
+/// there is no colour.
+#[must_use]
+pub fn bg_escape(color: Option<Color>) -> String {
+    color.map_or_else(String::new, |c| format!("\x1b[{}m", sgr(c, true)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{apply_optional_env, restore_optional_env};
+    use rstest::rstest;
+    use serial_test::serial;
+
+    #[rstest]
+    #[case(Color::DarkGreen, false, "32")]
+    #[case(Color::Red, true, "101")]
+    #[case(Color::AnsiValue(208), false, "38;5;208")]
+    #[case(Color::Rgb { r: 16, g: 56, b: 24 }, true, "48;2;16;56;24")]
+    fn sgr_codes(#[case] color: Color, #[case] background: bool, #[case] expected: &str) {
+        assert_eq!(sgr(color, background), expected);
+    }
+
+    #[test]
+    fn overrides_replace_theme_colours() {
+        let colours = ThemeColours {
+            added: Some(Colour(Color::Blue)),
+            author: Some(Colour(Color::Magenta)),
+            ..ThemeColours::default()
+        };
+        let palette = Theme::new(ThemeName::Light, colours).palette();
+        assert_eq!(palette.added_bg, Some(Color::Blue));
+        assert_eq!(palette.removed_bg, Palette::LIGHT.removed_bg);
+        assert_eq!(palette.author, Some(Color::Magenta));
+    }
+
+    #[test]
+    fn no_colour_ignores_overrides() {
+        let colours = ThemeColours {
+            added: Some(Colour(Color::Blue)),
+            ..ThemeColours::default()
+        };
+        let theme = Theme::new(ThemeName::NoColour, colours);
+        assert_eq!(theme.palette(), Palette::NO_COLOUR);
+        assert!(!theme.colours_diffs());
+    }
+
+    #[rstest]
+    #[case(Some("1"), ThemeName::NoColour)]
+    #[case(Some(""), ThemeName::Light)]
+    #[case(None, ThemeName::Light)]
+    #[serial]
+    fn no_color_overrides_configured_theme(
+        #[case] no_color: Option<&str>,
+        #[case] expected: ThemeName,
+    ) {
+        let original = environment::var("NO_COLOR").ok();
+        apply_optional_env("NO_COLOR", no_color);
+        let global = GlobalArgs {
+            theme: Some(ThemeName::Light),
+            ..GlobalArgs::default()
+        };
+        let theme = Theme::from_args(&global);
+        restore_optional_env("NO_COLOR", original);
+        assert_eq!(theme.name, expected);
+    }
+
+    #[test]
+    fn header_override_applies_to_skin() {
+        let colours = ThemeColours {
+            header: Some(Colour(Color::DarkMagenta)),
+            ..ThemeColours::default()
+        };
+        let skin = Theme::new(ThemeName::Dark, colours).skin(Icons::EMOJI);
+        assert!(
+            skin.headers
+                .iter()
+                .all(|h| h.compound_style.get_fg() == Some(Color::DarkMagenta))
+        );
+    }
+}
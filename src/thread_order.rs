@@ -0,0 +1,222 @@
+//! Sorting and grouping review threads for display.
+//!
+//! GitHub returns threads in the order they were created on the pull
+//! request's diff, which scatters a file's comments when reviewers come back
+//! to it. `--sort` reorders threads by a key taken from each thread's first
+//! comment, except `newest`, which looks at the latest reply so that
+//! threads with fresh activity come first. `--group-by` splits them under
+//! headings. Groups appear in the order of their first thread, so sorting
+//! also decides the group order.
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+
+use crate::cli_args::{GroupBy, ThreadSort};
+use crate::diff::comment_line;
+use crate::{ReviewComment, ReviewThread};
+
+/// Label used for threads whose first author is unknown.
+const UNKNOWN_AUTHOR: &str = "(unknown)";
+
+/// Threads sharing a file or author, in display order.
+#[derive(Debug)]
+pub struct ThreadGroup {
+    /// What the threads were grouped by.
+    pub by: GroupBy,
+    /// The shared file path or author login.
+    pub key: String,
+    /// Threads in the group.
+    pub threads: Vec<ReviewThread>,
+}
+
+impl ThreadGroup {
+    /// Describe the number of threads in the group, such as `2 threads`.
+    #[must_use]
+    pub fn count_label(&self) -> String {
+        let n = self.threads.len();
+        format!("{n} {}", if n == 1 { "thread" } else { "threads" })
+    }
+}
+
+/// Sort `threads` in place by `sort`.
+///
+/// `newest` orders threads by their most recent comment and `oldest` by
+/// their first, so a reply brings an old thread forward under `newest` but
+/// leaves its place under `oldest` unchanged. The sort is stable, so
+/// threads with equal keys keep GitHub's order. Threads missing the key,
+/// such as those without a timestamp, sort last.
+pub fn sort_threads(threads: &mut [ReviewThread], sort: ThreadSort) {
+    match sort {
+        ThreadSort::File => threads.sort_by(|a, b| by_first(a, b, |c| c.path.clone())),
+        ThreadSort::Line => threads.sort_by(|a, b| {
+            by_first(a, b, |c| c.path.clone())
+                .then_with(|| by_first(a, b, |c| comment_line(c).unwrap_or(u32::MAX)))
+        }),
+        ThreadSort::Author => threads.sort_by(|a, b| {
+            by_first(a, b, |c| {
+                c.author
+                    .as_ref()
+                    .map(|u| u.login.to_lowercase())
+                    .map_or((1, String::new()), |login| (0, login))
+            })
+        }),
+        ThreadSort::Newest => {
+            threads.sort_by_key(|t| latest_comment(t).map_or((1, 0), |at| (0, -at.timestamp())));
+        }
+        ThreadSort::Oldest => threads.sort_by(|a, b| {
+            by_first(a, b, |c| {
+                c.created_at.map_or((1, 0), |t| (0, t.timestamp()))
+            })
+        }),
+    }
+}
+
+/// When the most recent comment in `thread` was posted.
+fn latest_comment(thread: &ReviewThread) -> Option<DateTime<Utc>> {
+    thread
+        .comments
+        .nodes
+        .iter()
+        .filter_map(|c| c.created_at)
+        .max()
+}
+
+/// Compare two threads by `key` applied to their first comments, placing
+/// empty threads last.
+fn by_first<K: Ord>(
+    a: &ReviewThread,
+    b: &ReviewThread,
+    key: impl Fn(&ReviewComment) -> K,
+) -> Ordering {
+    let first = |t: &ReviewThread| t.comments.nodes.first().map(&key);
+    match (first(a), first(b)) {
+        (Some(x), Some(y)) => x.cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Split `threads` into groups keyed by `by`, ordered by each group's first
+/// thread.
+#[must_use]
+pub fn group_threads(threads: Vec<ReviewThread>, by: GroupBy) -> Vec<ThreadGroup> {
+    let mut groups: Vec<ThreadGroup> = Vec::new();
+    for thread in threads {
+        let key = group_key(&thread, by);
+        if let Some(group) = groups.iter_mut().find(|g| g.key == key) {
+            group.threads.push(thread);
+        } else {
+            groups.push(ThreadGroup {
+                by,
+                key,
+                threads: vec![thread],
+            });
+        }
+    }
+    groups
+}
+
+fn group_key(thread: &ReviewThread, by: GroupBy) -> String {
+    let first = thread.comments.nodes.first();
+    match by {
+        GroupBy::File => first.map(|c| c.path.clone()).unwrap_or_default(),
+        GroupBy::Author => first
+            .and_then(|c| c.author.as_ref())
+            .map_or_else(|| UNKNOWN_AUTHOR.to_owned(), |u| u.login.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CommentConnection, User};
+    use chrono::{TimeZone, Utc};
+    use rstest::rstest;
+
+    fn thread(path: &str, line: u32, login: &str, day: u32) -> ReviewThread {
+        let comment = ReviewComment {
+            path: path.into(),
+            diff_hunk: format!("@@ -{line} +{line} @@\n+x"),
+            author: Some(User {
+                login: login.into(),
+            }),
+            created_at: Utc.with_ymd_and_hms(2025, 1, day, 0, 0, 0).single(),
+            url: format!("{path}:{line}"),
+            ..Default::default()
+        };
+        ReviewThread {
+            comments: CommentConnection {
+                nodes: vec![comment],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn sample() -> Vec<ReviewThread> {
+        vec![
+            thread("b.rs", 9, "carol", 2),
+            thread("a.rs", 30, "Bob", 3),
+            thread("b.rs", 4, "alice", 1),
+            thread("a.rs", 7, "carol", 4),
+        ]
+    }
+
+    fn urls(threads: &[ReviewThread]) -> Vec<&str> {
+        threads
+            .iter()
+            .filter_map(|t| t.comments.nodes.first())
+            .map(|c| c.url.as_str())
+            .collect()
+    }
+
+    #[rstest]
+    #[case(ThreadSort::File, &["a.rs:30", "a.rs:7", "b.rs:9", "b.rs:4"])]
+    #[case(ThreadSort::Line, &["a.rs:7", "a.rs:30", "b.rs:4", "b.rs:9"])]
+    #[case(ThreadSort::Author, &["b.rs:4", "a.rs:30", "b.rs:9", "a.rs:7"])]
+    #[case(ThreadSort::Newest, &["a.rs:7", "a.rs:30", "b.rs:9", "b.rs:4"])]
+    #[case(ThreadSort::Oldest, &["b.rs:4", "b.rs:9", "a.rs:30", "a.rs:7"])]
+    fn threads_sort_by_key(#[case] sort: ThreadSort, #[case] expected: &[&str]) {
+        let mut threads = sample();
+        sort_threads(&mut threads, sort);
+        assert_eq!(urls(&threads), expected);
+    }
+
+    #[test]
+    fn undated_threads_sort_last() {
+        let mut threads = sample();
+        if let Some(c) = threads
+            .first_mut()
+            .and_then(|t| t.comments.nodes.first_mut())
+        {
+            c.created_at = None;
+        }
+        sort_threads(&mut threads, ThreadSort::Oldest);
+        assert_eq!(urls(&threads).last(), Some(&"b.rs:9"));
+    }
+
+    #[rstest]
+    #[case(ThreadSort::Newest, &["b.rs:4", "a.rs:7", "a.rs:30", "b.rs:9"])]
+    #[case(ThreadSort::Oldest, &["b.rs:4", "b.rs:9", "a.rs:30", "a.rs:7"])]
+    fn replies_move_threads_under_newest_only(#[case] sort: ThreadSort, #[case] expected: &[&str]) {
+        let mut threads = sample();
+        let reply = thread("b.rs", 4, "bob", 5).comments.nodes;
+        if let Some(t) = threads.get_mut(2) {
+            t.comments.nodes.extend(reply);
+        }
+        sort_threads(&mut threads, sort);
+        assert_eq!(urls(&threads), expected);
+    }
+
+    #[test]
+    fn groups_follow_first_appearance() {
+        let groups = group_threads(sample(), GroupBy::Author);
+        let keys: Vec<_> = groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, ["carol", "Bob", "alice"]);
+        let carol = groups.first().expect("carol group");
+        assert_eq!(urls(&carol.threads), ["b.rs:9", "a.rs:7"]);
+        assert_eq!(carol.count_label(), "2 threads");
+    }
+}
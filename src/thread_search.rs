@@ -0,0 +1,142 @@
+//! Searching review threads for a regular expression.
+//!
+//! `--grep` keeps the threads in which any comment body or diff hunk matches
+//! the pattern, so replies count as much as the comment that started the
+//! thread. `--ignore-case` makes the match case-insensitive. The search runs
+//! after the file and author filters. The terminal renderer shows what it
+//! found in reverse video and the Markdown renderer puts it in bold.
+
+use crate::cli_args::PrArgs;
+use crate::{ReviewComment, ReviewThread, VkError};
+use regex::{Regex, RegexBuilder};
+
+/// Pattern a thread must contain to be shown.
+#[derive(Debug, Default, Clone)]
+pub struct ThreadSearch {
+    pattern: Option<Regex>,
+}
+
+impl ThreadSearch {
+    /// Compile `pattern`, ignoring case when asked.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VkError::InvalidSearchPattern`] when `pattern` is not a
+    /// valid regular expression.
+    pub fn new(pattern: Option<&str>, ignore_case: bool) -> Result<Self, VkError> {
+        let Some(pattern) = pattern else {
+            return Ok(Self::default());
+        };
+        RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .map(|regex| Self {
+                pattern: Some(regex),
+            })
+            .map_err(|e| VkError::InvalidSearchPattern {
+                pattern: pattern.into(),
+                reason: e.to_string().into(),
+            })
+    }
+
+    /// Build the search from the `pr` options.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VkError::InvalidSearchPattern`] when `--grep` is not a valid
+    /// regular expression.
+    pub fn from_args(args: &PrArgs) -> Result<Self, VkError> {
+        Self::new(args.grep.as_deref(), args.ignore_case)
+    }
+
+    /// The compiled pattern, or `None` when no search was requested.
+    #[must_use]
+    pub const fn pattern(&self) -> Option<&Regex> {
+        self.pattern.as_ref()
+    }
+
+    /// Whether a search was requested.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.pattern.is_some()
+    }
+
+    /// Whether any comment in `thread` matches.
+    #[must_use]
+    pub fn keeps_thread(&self, thread: &ReviewThread) -> bool {
+        self.pattern
+            .as_ref()
+            .is_none_or(|re| thread.comments.nodes.iter().any(|c| comment_matches(re, c)))
+    }
+
+    /// Drop the threads that do not match.
+    #[must_use]
+    pub fn threads(&self, mut threads: Vec<ReviewThread>) -> Vec<ReviewThread> {
+        if self.is_active() {
+            threads.retain(|t| self.keeps_thread(t));
+        }
+        threads
+    }
+}
+
+fn comment_matches(pattern: &Regex, comment: &ReviewComment) -> bool {
+    pattern.is_match(&comment.body) || pattern.is_match(&comment.diff_hunk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CommentConnection;
+    use rstest::rstest;
+
+    fn thread(comments: &[(&str, &str)]) -> ReviewThread {
+        let nodes = comments
+            .iter()
+            .map(|(body, diff_hunk)| ReviewComment {
+                body: (*body).into(),
+                diff_hunk: (*diff_hunk).into(),
+                ..Default::default()
+            })
+            .collect();
+        ReviewThread {
+            comments: CommentConnection {
+                nodes,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case("unwrap", false, &[("Avoid unwrap here", "")], true)]
+    #[case("unwrap", false, &[("Looks fine", ""), ("but unwrap panics", "")], true)]
+    #[case("unwrap", false, &[("Looks fine", "@@ -1 +1 @@\n+x.unwrap()\n")], true)]
+    #[case("Security", false, &[("security hole", "")], false)]
+    #[case("Security", true, &[("security hole", "")], true)]
+    #[case(r"\bpanic!\(", false, &[("calls panic!(\"x\")", "")], true)]
+    fn threads_are_searched(
+        #[case] pattern: &str,
+        #[case] ignore_case: bool,
+        #[case] comments: &[(&str, &str)],
+        #[case] expected: bool,
+    ) {
+        let search = ThreadSearch::new(Some(pattern), ignore_case).expect("valid pattern");
+        assert_eq!(search.keeps_thread(&thread(comments)), expected);
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let err = ThreadSearch::new(Some("(unclosed"), false).expect_err("invalid regex");
+        assert!(
+            matches!(err, VkError::InvalidSearchPattern { .. }),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn no_pattern_keeps_everything() {
+        let search = ThreadSearch::default();
+        assert!(!search.is_active());
+        assert_eq!(search.threads(vec![thread(&[("x", "")])]).len(), 1);
+    }
+}
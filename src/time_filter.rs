@@ -0,0 +1,342 @@
+//! Filtering review threads and reviews by when they were posted.
+//!
+//! `--since` and `--until` each take a duration before now such as `2d` or
+//! `1w3d`, an ISO 8601 date or date-time, or a commit SHA. A thread is kept
+//! when any of its comments was posted inside the window, so a reply to an
+//! old thread brings the whole conversation back. Reviews are kept when they
+//! were submitted inside it.
+//!
+//! GitHub no longer reports when a commit was pushed, so a commit stands for
+//! its committer date. Amending or rebasing before a push updates that date,
+//! which makes it a close stand-in for the push itself.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use serde::Deserialize;
+use serde_json::{Map, json};
+
+use crate::graphql_queries::COMMIT_DATE_QUERY;
+use crate::ref_parser::RepoInfo;
+use crate::reviews::PullRequestReview;
+use crate::{GraphQLClient, ReviewThread, VkError};
+
+/// Shortest abbreviated commit SHA accepted.
+const MIN_SHA_LEN: usize = 7;
+/// Length of a full SHA-1 commit id.
+const MAX_SHA_LEN: usize = 40;
+
+/// A point in time given on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeSpec {
+    /// This long before now.
+    Ago(TimeDelta),
+    /// A fixed instant.
+    At(DateTime<Utc>),
+    /// When a commit was made.
+    Commit(String),
+}
+
+impl TimeSpec {
+    /// Parse a duration, date, date-time or commit SHA.
+    ///
+    /// Dates without a time mean midnight UTC, and date-times without an
+    /// offset are read as UTC.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VkError::InvalidTime`] when `value` is none of these.
+    pub fn parse(value: &str) -> Result<Self, VkError> {
+        let value = value.trim();
+        if let Some(at) = parse_date(value) {
+            return Ok(Self::At(at));
+        }
+        if is_commit_sha(value) {
+            return Ok(Self::Commit(value.to_owned()));
+        }
+        parse_duration(value)
+            .map(Self::Ago)
+            .ok_or_else(|| VkError::InvalidTime {
+                value: value.into(),
+            })
+    }
+
+    /// Resolve the instant this spec names, relative to `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VkError::CommitNotFound`] when a commit cannot be found in
+    /// `repo`, or an error when the API request fails.
+    pub async fn resolve(
+        &self,
+        client: &GraphQLClient,
+        repo: &RepoInfo,
+        now: DateTime<Utc>,
+    ) -> Result<DateTime<Utc>, VkError> {
+        match self {
+            Self::Ago(delta) => Ok(now - *delta),
+            Self::At(at) => Ok(*at),
+            Self::Commit(sha) => fetch_commit_date(client, repo, sha).await,
+        }
+    }
+}
+
+/// Window of time a thread or review must fall in to be shown.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TimeFilter {
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+impl TimeFilter {
+    /// Keep items posted after `since` and before `until`.
+    #[must_use]
+    pub const fn new(since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> Self {
+        Self { since, until }
+    }
+
+    /// Resolve the `--since` and `--until` specs against `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when a commit cannot be looked up.
+    pub async fn resolve(
+        since: Option<&TimeSpec>,
+        until: Option<&TimeSpec>,
+        client: &GraphQLClient,
+        repo: &RepoInfo,
+        now: DateTime<Utc>,
+    ) -> Result<Self, VkError> {
+        let since = match since {
+            Some(spec) => Some(spec.resolve(client, repo, now).await?),
+            None => None,
+        };
+        let until = match until {
+            Some(spec) => Some(spec.resolve(client, repo, now).await?),
+            None => None,
+        };
+        Ok(Self::new(since, until))
+    }
+
+    /// Whether either bound is set.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
+
+    /// Whether an item posted at `at` falls inside the window.
+    ///
+    /// Items without a timestamp only pass when no bound is set.
+    #[must_use]
+    pub fn allows(&self, at: Option<DateTime<Utc>>) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        at.is_some_and(|at| {
+            self.since.is_none_or(|since| at > since) && self.until.is_none_or(|until| at < until)
+        })
+    }
+
+    /// Drop the threads with no comment inside the window.
+    #[must_use]
+    pub fn threads(&self, mut threads: Vec<ReviewThread>) -> Vec<ReviewThread> {
+        if self.is_active() {
+            threads.retain(|t| t.comments.nodes.iter().any(|c| self.allows(c.created_at)));
+        }
+        threads
+    }
+
+    /// Drop the reviews submitted outside the window.
+    #[must_use]
+    pub fn reviews(&self, mut reviews: Vec<PullRequestReview>) -> Vec<PullRequestReview> {
+        if self.is_active() {
+            reviews.retain(|r| self.allows(r.submitted_at));
+        }
+        reviews
+    }
+}
+
+/// Parse an RFC 3339 date-time, or a date or date-time without an offset.
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Some(at.with_timezone(&Utc));
+    }
+    [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })
+    .map(|at| at.and_utc())
+}
+
+/// Whether `value` looks like a full or abbreviated commit SHA.
+fn is_commit_sha(value: &str) -> bool {
+    (MIN_SHA_LEN..=MAX_SHA_LEN).contains(&value.len())
+        && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Parse a duration such as `90m`, `2d` or `1w3d`.
+///
+/// Units are `s`, `m`, `h`, `d` and `w`; every number needs one.
+fn parse_duration(value: &str) -> Option<TimeDelta> {
+    let mut total: i64 = 0;
+    let mut amount: Option<i64> = None;
+    for c in value.chars() {
+        if let Some(digit) = c.to_digit(10) {
+            let next = amount
+                .unwrap_or(0)
+                .checked_mul(10)?
+                .checked_add(i64::from(digit))?;
+            amount = Some(next);
+            continue;
+        }
+        let seconds = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        total = total.checked_add(amount.take()?.checked_mul(seconds)?)?;
+    }
+    if amount.is_some() || value.is_empty() {
+        return None;
+    }
+    TimeDelta::try_seconds(total)
+}
+
+#[derive(Deserialize)]
+struct CommitData {
+    repository: CommitRepository,
+}
+
+#[derive(Deserialize)]
+struct CommitRepository {
+    object: Option<CommitObject>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitObject {
+    committed_date: Option<DateTime<Utc>>,
+}
+
+/// Look up when commit `sha` was made in `repo`.
+async fn fetch_commit_date(
+    client: &GraphQLClient,
+    repo: &RepoInfo,
+    sha: &str,
+) -> Result<DateTime<Utc>, VkError> {
+    let mut vars = Map::new();
+    vars.insert("owner".into(), json!(repo.owner.clone()));
+    vars.insert("name".into(), json!(repo.name.clone()));
+    vars.insert("expression".into(), json!(sha));
+    let data: CommitData = client.fetch_page(COMMIT_DATE_QUERY, None, vars).await?;
+    data.repository
+        .object
+        .and_then(|o| o.committed_date)
+        .ok_or_else(|| VkError::CommitNotFound { sha: sha.into() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::start_server;
+    use crate::{CommentConnection, ReviewComment};
+    use rstest::rstest;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .expect("valid timestamp")
+            .with_timezone(&Utc)
+    }
+
+    fn thread(posted: &[&str]) -> ReviewThread {
+        let nodes = posted
+            .iter()
+            .map(|p| ReviewComment {
+                created_at: Some(at(p)),
+                ..Default::default()
+            })
+            .collect();
+        ReviewThread {
+            comments: CommentConnection {
+                nodes,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case("2d", TimeSpec::Ago(TimeDelta::days(2)))]
+    #[case("1w3d", TimeSpec::Ago(TimeDelta::days(10)))]
+    #[case("90m", TimeSpec::Ago(TimeDelta::minutes(90)))]
+    #[case("2024-05-01", TimeSpec::At(at("2024-05-01T00:00:00Z")))]
+    #[case("2024-05-01T12:30", TimeSpec::At(at("2024-05-01T12:30:00Z")))]
+    #[case("2024-05-01T12:30:00+02:00", TimeSpec::At(at("2024-05-01T10:30:00Z")))]
+    #[case("1a2b3c4", TimeSpec::Commit("1a2b3c4".into()))]
+    fn specs_are_parsed(#[case] value: &str, #[case] expected: TimeSpec) {
+        assert_eq!(TimeSpec::parse(value).expect("valid spec"), expected);
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("2")]
+    #[case("d")]
+    #[case("2x")]
+    #[case("2024-13-01")]
+    #[case("yesterday")]
+    fn bad_specs_are_rejected(#[case] value: &str) {
+        let err = TimeSpec::parse(value).expect_err("invalid spec");
+        assert!(matches!(err, VkError::InvalidTime { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn threads_match_any_comment_in_window() {
+        let filter = TimeFilter::new(
+            Some(at("2024-05-01T00:00:00Z")),
+            Some(at("2024-06-01T00:00:00Z")),
+        );
+        let threads = vec![
+            thread(&["2024-04-01T00:00:00Z", "2024-05-02T00:00:00Z"]),
+            thread(&["2024-04-01T00:00:00Z"]),
+            thread(&["2024-07-01T00:00:00Z"]),
+        ];
+        assert_eq!(filter.threads(threads).len(), 1);
+        assert!(!filter.allows(None));
+        assert!(TimeFilter::default().allows(None));
+    }
+
+    #[tokio::test]
+    async fn commits_resolve_to_their_date() {
+        let found =
+            json!({"data": {"repository": {"object": {"committedDate": "2024-05-01T10:00:00Z"}}}});
+        let missing = json!({"data": {"repository": {"object": null}}});
+        let server = start_server(vec![found.to_string(), missing.to_string()]);
+        let repo = RepoInfo {
+            owner: "o".into(),
+            name: "r".into(),
+        };
+        let spec = TimeSpec::Commit("abc1234".into());
+        let date = spec
+            .resolve(&server.client, &repo, Utc::now())
+            .await
+            .expect("commit date");
+        assert_eq!(date, at("2024-05-01T10:00:00Z"));
+        let err = spec
+            .resolve(&server.client, &repo, Utc::now())
+            .await
+            .expect_err("missing commit");
+        assert!(matches!(err, VkError::CommitNotFound { .. }), "{err:?}");
+        server.join.abort();
+        let _ = server.join.await;
+    }
+}
@@ -0,0 +1,114 @@
+//! Describe when comments and reviews were posted.
+//!
+//! Author banners carry the posting time so stale feedback can be told apart
+//! from fresh comments. Relative times are rounded down to the largest whole
+//! unit, which is precise enough for triage and keeps banners short.
+
+use chrono::{DateTime, Utc};
+
+use crate::cli_args::TimestampStyle;
+
+/// Days counted as a month or a year in relative times.
+const DAYS_PER_MONTH: i64 = 30;
+const DAYS_PER_YEAR: i64 = 365;
+
+/// Describe `then` for an author banner, such as `3 days ago` or
+/// `on 2025-01-03 14:05 UTC`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use chrono::{Duration, Utc};
+/// let now = Utc::now();
+/// assert_eq!(describe_time(now - Duration::days(3), now, TimestampStyle::Relative), "3 days ago");
+/// ```
+#[must_use]
+pub fn describe_time(then: DateTime<Utc>, now: DateTime<Utc>, style: TimestampStyle) -> String {
+    match style {
+        TimestampStyle::Relative => relative_time(then, now),
+        TimestampStyle::Absolute => format!("on {}", then.format("%Y-%m-%d %H:%M UTC")),
+    }
+}
+
+/// Describe how long before `now` the instant `then` was.
+///
+/// Times in the future, which only arise from clock skew, read as `just now`.
+fn relative_time(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = now - then;
+    let days = age.num_days();
+    let (count, unit) = if age.num_minutes() < 1 {
+        return "just now".to_owned();
+    } else if age.num_hours() < 1 {
+        (age.num_minutes(), "minute")
+    } else if days < 1 {
+        (age.num_hours(), "hour")
+    } else if days < DAYS_PER_MONTH {
+        (days, "day")
+    } else if days < DAYS_PER_YEAR {
+        (days.div_euclid(DAYS_PER_MONTH), "month")
+    } else {
+        (days.div_euclid(DAYS_PER_YEAR), "year")
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
+
+/// Whether a comment edited at `last_edited` was changed after it was
+/// posted at `created`.
+///
+/// GitHub sets `lastEditedAt` only for edited comments; the comparison guards
+/// against services that report it for every comment.
+#[must_use]
+pub fn edited_after_posting(
+    created: Option<DateTime<Utc>>,
+    last_edited: Option<DateTime<Utc>>,
+) -> bool {
+    match (created, last_edited) {
+        (Some(created), Some(edited)) => edited > created,
+        (None, Some(_)) => true,
+        (_, None) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Duration::seconds(-30), "just now")]
+    #[case(Duration::seconds(59), "just now")]
+    #[case(Duration::minutes(1), "1 minute ago")]
+    #[case(Duration::hours(5), "5 hours ago")]
+    #[case(Duration::days(3), "3 days ago")]
+    #[case(Duration::days(65), "2 months ago")]
+    #[case(Duration::days(400), "1 year ago")]
+    fn relative_times_round_down(#[case] age: Duration, #[case] expected: &str) {
+        let now = Utc::now();
+        assert_eq!(
+            describe_time(now - age, now, TimestampStyle::Relative),
+            expected
+        );
+    }
+
+    #[test]
+    fn absolute_times_use_utc() {
+        let then = Utc
+            .with_ymd_and_hms(2025, 1, 3, 14, 5, 9)
+            .single()
+            .expect("valid time");
+        assert_eq!(
+            describe_time(then, Utc::now(), TimestampStyle::Absolute),
+            "on 2025-01-03 14:05 UTC"
+        );
+    }
+
+    #[test]
+    fn edits_must_follow_posting() {
+        let created = Utc::now() - Duration::days(1);
+        assert!(edited_after_posting(Some(created), Some(Utc::now())));
+        assert!(!edited_after_posting(Some(created), Some(created)));
+        assert!(!edited_after_posting(Some(created), None));
+    }
+}
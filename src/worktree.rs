@@ -0,0 +1,510 @@
+//! Translate review comment lines into the local working tree.
+//!
+//! Comments refer to lines at the pull request head commit, which drift as
+//! the reviewed files are edited locally. The head version of each file is
+//! read with `git show` and diffed against the working tree using `diffy`,
+//! so each commented line can be followed to its current position, to the
+//! line that replaced it, or reported as deleted.
+//!
+//! Threads can also be classified by whether the commented code still reads
+//! the same in the working tree, which needs only the diff hunk and the file
+//! on disk.
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use diffy::Line;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::diff::{comment_line, commented_region};
+use crate::ref_parser::file_at_revision;
+use crate::{ReviewComment, ReviewThread};
+
+/// Where a commented line now sits in the working tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum LocalLine {
+    /// The line still exists, at this line number.
+    Present { line: u32 },
+    /// The line was rewritten locally; its replacement is at this line
+    /// number.
+    Edited { line: u32 },
+    /// The line, or its whole file, has been deleted locally.
+    Deleted,
+}
+
+impl LocalLine {
+    /// Describe the working-tree location of a comment on `path`, such as
+    /// `local src/lib.rs:42` or `lines deleted locally`.
+    #[must_use]
+    pub fn describe(self, path: &str) -> String {
+        match self {
+            Self::Present { line } => format!("local {path}:{line}"),
+            Self::Edited { line } => format!("local {path}:{line}, edited"),
+            Self::Deleted => "lines deleted locally".to_owned(),
+        }
+    }
+
+    /// The working-tree line number, unless the line was deleted.
+    #[must_use]
+    pub const fn line(self) -> Option<u32> {
+        match self {
+            Self::Present { line } | Self::Edited { line } => Some(line),
+            Self::Deleted => None,
+        }
+    }
+}
+
+/// Whether a thread's commented code has changed in the working tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LocalStatus {
+    /// The commented lines are still present, unchanged.
+    Untouched,
+    /// Some of the commented lines were edited.
+    ModifiedLocally,
+    /// None of the commented lines remain, or the file is gone.
+    LinesRemoved,
+}
+
+impl LocalStatus {
+    /// Human-readable label, such as `modified locally`.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Untouched => "untouched",
+            Self::ModifiedLocally => "modified locally",
+            Self::LinesRemoved => "lines removed",
+        }
+    }
+}
+
+/// Describe what the working tree says about `comment`, combining its mapped
+/// line and the thread's `status`, or `None` when neither is known.
+#[must_use]
+pub fn local_note(comment: &ReviewComment, status: Option<LocalStatus>) -> Option<String> {
+    let parts: Vec<String> = comment
+        .local_line
+        .map(|local| local.describe(&comment.path))
+        .into_iter()
+        .chain(status.map(|s| s.label().to_owned()))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+/// Follow `line` (1-based) of `head` to its position in `local`.
+///
+/// A line removed in a change that also inserts lines counts as edited and
+/// maps to the inserted line in the same relative position; only a change
+/// that inserts nothing deletes it.
+///
+/// # Examples
+///
+/// ```ignore
+/// use vk::worktree::{LocalLine, map_line};
+/// assert_eq!(map_line("a\nb\n", "new\na\nb\n", 2), LocalLine::Present { line: 3 });
+/// ```
+#[must_use]
+pub fn map_line(head: &str, local: &str, line: u32) -> LocalLine {
+    let Ok(target) = usize::try_from(line) else {
+        return LocalLine::Deleted;
+    };
+    let patch = diffy::create_patch(head, local);
+    let mut inserted = 0_usize;
+    let mut deleted = 0_usize;
+    for hunk in patch.hunks() {
+        if target < hunk.old_range().start() {
+            break;
+        }
+        let mut old_no = hunk.old_range().start();
+        let mut new_no = hunk.new_range().start();
+        for (at, diff_line) in hunk.lines().iter().enumerate() {
+            match diff_line {
+                Line::Context(_) => {
+                    if old_no == target {
+                        return present(new_no);
+                    }
+                    old_no += 1;
+                    new_no += 1;
+                }
+                Line::Delete(_) => {
+                    if old_no == target {
+                        return replacement_line(hunk.lines(), at, new_no);
+                    }
+                    old_no += 1;
+                    deleted += 1;
+                }
+                Line::Insert(_) => {
+                    new_no += 1;
+                    inserted += 1;
+                }
+            }
+        }
+    }
+    present((target + inserted).saturating_sub(deleted))
+}
+
+/// Map the deleted line at index `at` of `lines` to the line that replaced
+/// it, where `new_no` is the local line number reached at `at`.
+///
+/// The change run is the stretch of deletions and insertions around `at`.
+/// The `k`th deleted line maps to the `k`th inserted one, or to the last
+/// inserted line when the run deletes more than it inserts.
+fn replacement_line(lines: &[Line<'_, str>], at: usize, new_no: usize) -> LocalLine {
+    let is_context = |l: &Line<'_, str>| matches!(l, Line::Context(_));
+    let is_insert = |l: &&Line<'_, str>| matches!(l, Line::Insert(_));
+    let earlier = lines.get(..at).unwrap_or_default();
+    let later = lines.get(at..).unwrap_or_default();
+    let run_before = earlier
+        .iter()
+        .rposition(is_context)
+        .map_or(earlier, |p| earlier.get(p + 1..).unwrap_or_default());
+    let run_after = later
+        .iter()
+        .position(is_context)
+        .map_or(later, |p| later.get(..p).unwrap_or_default());
+    let inserted_before = run_before.iter().filter(is_insert).count();
+    let deleted_before = run_before.len() - inserted_before;
+    let inserted = inserted_before + run_after.iter().filter(is_insert).count();
+    if inserted == 0 {
+        return LocalLine::Deleted;
+    }
+    let line = new_no - inserted_before + deleted_before.min(inserted - 1);
+    u32::try_from(line).map_or(LocalLine::Deleted, |line| LocalLine::Edited { line })
+}
+
+fn present(line: usize) -> LocalLine {
+    u32::try_from(line).map_or(LocalLine::Deleted, |line| LocalLine::Present { line })
+}
+
+/// A local checkout compared against a pull request head commit.
+pub struct LocalCheckout {
+    root: PathBuf,
+    head_oid: String,
+    cache: HashMap<String, Option<FileVersions>>,
+}
+
+/// Head and working-tree contents of one file. `local` is `None` when the
+/// file has been removed from the working tree.
+struct FileVersions {
+    head: String,
+    local: Option<String>,
+}
+
+impl LocalCheckout {
+    /// Compare the working tree rooted at `root` with commit `head_oid`.
+    #[must_use]
+    pub fn new(root: PathBuf, head_oid: String) -> Self {
+        Self {
+            root,
+            head_oid,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Map `line` of `path` at the head commit into the working tree.
+    ///
+    /// Returns `None` when the head version of the file is unavailable,
+    /// usually because the commit has not been fetched, or when the local
+    /// file exists but cannot be read.
+    pub fn map(&mut self, path: &str, line: u32) -> Option<LocalLine> {
+        let versions = self
+            .cache
+            .entry(path.to_owned())
+            .or_insert_with(|| {
+                let head = file_at_revision(&self.root, &self.head_oid, path)?;
+                let local = read_local(&self.root, path).ok()?;
+                Some(FileVersions { head, local })
+            })
+            .as_ref()?;
+        Some(
+            versions
+                .local
+                .as_deref()
+                .map_or(LocalLine::Deleted, |local| {
+                    map_line(&versions.head, local, line)
+                }),
+        )
+    }
+}
+
+/// Read the working-tree copy of `path` below `root`.
+///
+/// Returns `Ok(None)` when the file has been removed, and logs any other
+/// failure, such as missing permissions, before returning it. Bytes that are
+/// not UTF-8 are replaced rather than treated as a failure.
+fn read_local(root: &Path, path: &str) -> Result<Option<String>, ErrorKind> {
+    match std::fs::read(root.join(path)) {
+        Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => {
+            warn!("could not read {path}: {e}");
+            Err(e.kind())
+        }
+    }
+}
+
+/// Record the working-tree line for the first comment of each thread.
+///
+/// Only comments that still apply to the head commit (those with a
+/// `position`) are mapped. Returns the number of comments that could not be
+/// mapped because the head version of their file was unavailable or the
+/// local file could not be read.
+pub fn map_threads(checkout: &mut LocalCheckout, threads: &mut [ReviewThread]) -> usize {
+    let mut unavailable = 0;
+    for thread in threads {
+        let Some(first) = thread.comments.nodes.first_mut() else {
+            continue;
+        };
+        if first.position.is_none() {
+            continue;
+        }
+        let Some(line) = comment_line(first) else {
+            continue;
+        };
+        first.local_line = checkout.map(&first.path, line);
+        if first.local_line.is_none() {
+            unavailable += 1;
+        }
+    }
+    unavailable
+}
+
+/// Lines of commented code compared with the working tree.
+const REGION_LINES: usize = 3;
+
+/// Classify `region`, which ends at the commented `line` (1-based), against
+/// `local`, the file's working-tree contents.
+///
+/// The region is untouched when it appears as a contiguous block exactly once
+/// in the file, so code that merely moved is not reported. A block repeated
+/// elsewhere, such as boilerplate, only counts when one copy still ends at
+/// `line`. Otherwise the region counts as modified while any of its
+/// non-blank lines survive, and as removed when none do or the file is
+/// missing. Returns `None` for an empty region.
+#[must_use]
+pub fn classify_region(
+    region: &[String],
+    line: Option<u32>,
+    local: Option<&str>,
+) -> Option<LocalStatus> {
+    if region.is_empty() {
+        return None;
+    }
+    let Some(local) = local else {
+        return Some(LocalStatus::LinesRemoved);
+    };
+    let region: Vec<&str> = region.iter().map(|l| l.trim_end()).collect();
+    let file: Vec<&str> = local.lines().map(str::trim_end).collect();
+    let ends: Vec<usize> = file
+        .windows(region.len())
+        .enumerate()
+        .filter(|(_, window)| *window == region.as_slice())
+        .map(|(start, _)| start + region.len())
+        .collect();
+    let anchored = line
+        .and_then(|line| usize::try_from(line).ok())
+        .is_some_and(|line| ends.contains(&line));
+    if ends.len() == 1 || anchored {
+        return Some(LocalStatus::Untouched);
+    }
+    let survives = region
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .any(|line| file.contains(line));
+    Some(if survives {
+        LocalStatus::ModifiedLocally
+    } else {
+        LocalStatus::LinesRemoved
+    })
+}
+
+/// Classify every unresolved thread against the working tree at `root`.
+///
+/// Each file is read once. Resolved threads, threads whose hunk has no
+/// commented code, and threads on files that cannot be read are left
+/// unclassified.
+pub fn classify_threads(root: &Path, threads: &mut [ReviewThread]) {
+    let mut files: HashMap<String, Result<Option<String>, ErrorKind>> = HashMap::new();
+    for thread in threads {
+        if thread.is_resolved {
+            continue;
+        }
+        let Some(first) = thread.comments.nodes.first() else {
+            continue;
+        };
+        let Ok(local) = files
+            .entry(first.path.clone())
+            .or_insert_with(|| read_local(root, &first.path))
+        else {
+            continue;
+        };
+        thread.local_status = classify_region(
+            &commented_region(first, REGION_LINES),
+            comment_line(first),
+            local.as_deref(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::GitRepoFixture;
+    use crate::{CommentConnection, ReviewComment};
+    use rstest::rstest;
+
+    const HEAD: &str = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n";
+
+    #[rstest]
+    #[case::unchanged(HEAD, 4, LocalLine::Present { line: 4 })]
+    #[case::lines_added_above("zero\nhalf\none\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n", 4, LocalLine::Present { line: 6 })]
+    #[case::line_removed_above("two\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n", 4, LocalLine::Present { line: 3 })]
+    #[case::target_deleted(
+        "one\ntwo\nthree\nfive\nsix\nseven\neight\nnine\nten\n",
+        4,
+        LocalLine::Deleted
+    )]
+    #[case::target_edited(
+        "one\ntwo\nthree\nFOUR\nfive\nsix\nseven\neight\nnine\nten\n",
+        4,
+        LocalLine::Edited { line: 4 }
+    )]
+    #[case::target_edited_below_insert(
+        "zero\none\ntwo\nthree\nFOUR\nfive\nsix\nseven\neight\nnine\nten\n",
+        4,
+        LocalLine::Edited { line: 5 }
+    )]
+    #[case::run_shrinks(
+        "one\ntwo\nTHREE-FOUR\nfive\nsix\nseven\neight\nnine\nten\n",
+        4,
+        LocalLine::Edited { line: 3 }
+    )]
+    #[case::change_far_below("one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\neleven\n", 2, LocalLine::Present { line: 2 })]
+    #[case::change_far_above("ONE\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n", 10, LocalLine::Present { line: 10 })]
+    fn map_line_follows_edits(#[case] local: &str, #[case] line: u32, #[case] expected: LocalLine) {
+        assert_eq!(map_line(HEAD, local, line), expected);
+    }
+
+    fn thread(path: &str, hunk: &str) -> ReviewThread {
+        ReviewThread {
+            comments: CommentConnection {
+                nodes: vec![ReviewComment {
+                    diff_hunk: hunk.into(),
+                    position: Some(2),
+                    path: path.into(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn local_line(thread: &ReviewThread) -> Option<LocalLine> {
+        thread.comments.nodes.first().and_then(|c| c.local_line)
+    }
+
+    fn region(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|&l| l.to_owned()).collect()
+    }
+
+    #[rstest]
+    #[case::unchanged(HEAD, Some(LocalStatus::Untouched))]
+    #[case::moved("zero\nthree\nfour\nfive\n", Some(LocalStatus::Untouched))]
+    #[case::trailing_whitespace("three  \nfour\nfive\n", Some(LocalStatus::Untouched))]
+    #[case::edited("three\nFOUR\nfive\n", Some(LocalStatus::ModifiedLocally))]
+    #[case::removed("one\ntwo\nsix\n", Some(LocalStatus::LinesRemoved))]
+    #[case::repeated_at_line(
+        "one\ntwo\nthree\nfour\nfive\nthree\nfour\nfive\n",
+        Some(LocalStatus::Untouched)
+    )]
+    #[case::repeated_elsewhere(
+        "three\nfour\nfive\nthree\nfour\nfive\nsix\n",
+        Some(LocalStatus::ModifiedLocally)
+    )]
+    fn classify_region_compares_with_file(
+        #[case] local: &str,
+        #[case] expected: Option<LocalStatus>,
+    ) {
+        let commented = region(&["three", "four", "five"]);
+        assert_eq!(classify_region(&commented, Some(5), Some(local)), expected);
+    }
+
+    #[test]
+    fn classify_region_handles_missing_inputs() {
+        assert_eq!(
+            classify_region(&region(&["x"]), Some(1), None),
+            Some(LocalStatus::LinesRemoved)
+        );
+        assert_eq!(classify_region(&[], Some(1), Some(HEAD)), None);
+    }
+
+    #[test]
+    fn classify_threads_skips_resolved_threads() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("lib.rs"), "a\nB\n").expect("write");
+        let mut resolved = thread("lib.rs", "@@ -1,2 +1,2 @@\n a\n b");
+        resolved.is_resolved = true;
+        std::fs::create_dir(dir.path().join("unreadable.rs")).expect("mkdir");
+        std::fs::write(dir.path().join("latin1.rs"), b"a\nb\n// caf\xe9\n").expect("write");
+        let mut threads = vec![
+            thread("lib.rs", "@@ -1,2 +1,2 @@\n a\n b"),
+            thread("missing.rs", "@@ -1,2 +1,2 @@\n a\n b"),
+            resolved,
+            thread("unreadable.rs", "@@ -1,2 +1,2 @@\n a\n b"),
+            thread("latin1.rs", "@@ -1,2 +1,2 @@\n a\n b"),
+        ];
+        classify_threads(dir.path(), &mut threads);
+        let statuses: Vec<_> = threads.iter().map(|t| t.local_status).collect();
+        assert_eq!(
+            statuses,
+            [
+                Some(LocalStatus::ModifiedLocally),
+                Some(LocalStatus::LinesRemoved),
+                None,
+                None,
+                Some(LocalStatus::Untouched),
+            ]
+        );
+    }
+
+    #[test]
+    fn map_threads_reads_head_from_git() {
+        let fixture = GitRepoFixture::on_branch("main")
+            .and_then(|f| f.with_committed_file("src/lib.rs", "a\nb\nc\n"))
+            .and_then(|f| f.with_committed_file("gone.rs", "x\ny\n"))
+            .and_then(|f| f.with_committed_file("swapped.rs", "a\nb\n"))
+            .and_then(|f| f.with_committed_file("latin1.rs", "a\nb\n"))
+            .expect("build fixture");
+        let oid = fixture.head_oid().expect("head oid");
+        std::fs::write(fixture.path().join("src/lib.rs"), "new\na\nb\nc\n").expect("edit");
+        std::fs::remove_file(fixture.path().join("gone.rs")).expect("remove");
+        std::fs::remove_file(fixture.path().join("swapped.rs")).expect("remove");
+        std::fs::create_dir(fixture.path().join("swapped.rs")).expect("mkdir");
+        std::fs::write(fixture.path().join("latin1.rs"), b"caf\xe9\na\nb\n").expect("edit");
+
+        let mut checkout = LocalCheckout::new(fixture.path().to_path_buf(), oid);
+        let hunk = "@@ -1,2 +1,2 @@\n a\n b";
+        let mut threads = vec![
+            thread("src/lib.rs", hunk),
+            thread("gone.rs", hunk),
+            thread("untracked.rs", hunk),
+            thread("swapped.rs", hunk),
+            thread("latin1.rs", hunk),
+        ];
+        let unavailable = map_threads(&mut checkout, &mut threads);
+
+        let [lib, gone, untracked, swapped, latin1] = threads.as_slice() else {
+            panic!("expected five threads");
+        };
+        assert_eq!(local_line(lib), Some(LocalLine::Present { line: 3 }));
+        assert_eq!(local_line(gone), Some(LocalLine::Deleted));
+        assert_eq!(local_line(untracked), None);
+        assert_eq!(local_line(swapped), None);
+        assert_eq!(local_line(latin1), Some(LocalLine::Present { line: 3 }));
+        assert_eq!(unavailable, 2);
+    }
+}
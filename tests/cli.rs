@@ -143,6 +143,215 @@ async fn pr_outputs_banner_when_threads_present() {
     shutdown.shutdown().await;
 }
 
+#[tokio::test]
+async fn pr_outputs_json_document() {
+    let (addr, handler, shutdown) = start_mitm().await.expect("start server");
+    let threads_body = include_str!("fixtures/review_threads_multiple_files.json").to_string();
+    let reviews_body = include_str!("fixtures/reviews_empty.json").to_string();
+    let last = reviews_body.clone();
+    let mut responses = vec![threads_body, reviews_body].into_iter();
+    *handler.lock().expect("lock handler") = Box::new(move |_req| {
+        let body = responses.next().unwrap_or_else(|| last.clone());
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "application/json")
+            .body(Full::from(body))
+            .expect("build response")
+    });
+
+    let stdout = tokio::task::spawn_blocking(move || {
+        let mut cmd = vk_cmd(addr);
+        cmd.args([
+            "pr",
+            "https://github.com/leynos/shared-actions/pull/42",
+            "--format",
+            "json",
+        ]);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        String::from_utf8(output).expect("utf8")
+    })
+    .await
+    .expect("spawn blocking");
+
+    assert!(
+        !stdout.contains(START_BANNER),
+        "JSON output must not include banners: {stdout}"
+    );
+    let document: serde_json::Value = serde_json::from_str(&stdout).expect("parse json output");
+    assert_eq!(document.pointer("/schemaVersion"), Some(&json!(1)));
+    assert_eq!(
+        document.pointer("/pullRequest/owner"),
+        Some(&json!("leynos"))
+    );
+    assert_eq!(
+        document.pointer("/pullRequest/repo"),
+        Some(&json!("shared-actions"))
+    );
+    assert_eq!(document.pointer("/pullRequest/number"), Some(&json!(42)));
+    assert_eq!(
+        document.pointer("/summary/0/path"),
+        Some(&json!("src/lib.rs"))
+    );
+    assert_eq!(document.pointer("/summary/0/comments"), Some(&json!(2)));
+    let threads = document
+        .get("threads")
+        .and_then(serde_json::Value::as_array)
+        .expect("threads array");
+    assert!(!threads.is_empty());
+    assert!(
+        threads
+            .iter()
+            .all(|t| t.get("comments").is_some_and(serde_json::Value::is_array))
+    );
+
+    shutdown.shutdown().await;
+}
+
+#[tokio::test]
+async fn pr_outputs_sarif_log() {
+    let (addr, handler, shutdown) = start_mitm().await.expect("start server");
+    let threads_body = include_str!("fixtures/review_threads_multiple_files.json").to_string();
+    *handler.lock().expect("lock handler") = Box::new(move |_req| {
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "application/json")
+            .body(Full::from(threads_body.clone()))
+            .expect("build response")
+    });
+
+    let stdout = tokio::task::spawn_blocking(move || {
+        let mut cmd = vk_cmd(addr);
+        cmd.args([
+            "pr",
+            "https://github.com/leynos/shared-actions/pull/42",
+            "--format",
+            "sarif",
+        ]);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        String::from_utf8(output).expect("utf8")
+    })
+    .await
+    .expect("spawn blocking");
+
+    let log: serde_json::Value = serde_json::from_str(&stdout).expect("parse sarif output");
+    assert_eq!(log.pointer("/version"), Some(&json!("2.1.0")));
+    let results = log
+        .pointer("/runs/0/results")
+        .and_then(serde_json::Value::as_array)
+        .expect("results array");
+    assert!(!results.is_empty(), "expected one result per thread");
+    for result in results {
+        assert!(
+            result
+                .pointer("/locations/0/physicalLocation/artifactLocation/uri")
+                .and_then(serde_json::Value::as_str)
+                .is_some_and(|uri| !uri.is_empty()),
+            "result without location: {result}"
+        );
+        assert!(result.pointer("/properties/permalink").is_some());
+    }
+
+    shutdown.shutdown().await;
+}
+
+#[tokio::test]
+async fn pr_streams_ndjson_events() {
+    let (addr, handler, shutdown) = start_mitm().await.expect("start server");
+    let threads_body = include_str!("fixtures/review_threads_multiple_files.json").to_string();
+    let reviews_body = include_str!("fixtures/reviews_empty.json").to_string();
+    // Reviews are fetched before threads when streaming.
+    let mut responses = vec![reviews_body, threads_body].into_iter();
+    *handler.lock().expect("lock handler") = Box::new(move |_req| {
+        let body = responses.next().expect("response");
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "application/json")
+            .body(Full::from(body))
+            .expect("build response")
+    });
+
+    let stdout = tokio::task::spawn_blocking(move || {
+        let mut cmd = vk_cmd(addr);
+        cmd.args([
+            "pr",
+            "https://github.com/leynos/shared-actions/pull/42",
+            "--format",
+            "ndjson",
+        ]);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        String::from_utf8(output).expect("utf8")
+    })
+    .await
+    .expect("spawn blocking");
+
+    let events: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("parse event line"))
+        .collect();
+    let types: Vec<_> = events
+        .iter()
+        .filter_map(|e| e.get("type").and_then(serde_json::Value::as_str))
+        .collect();
+    let threads = types.iter().filter(|t| **t == "thread").count();
+    assert!(threads > 0, "expected thread events: {types:?}");
+    assert_eq!(types.first(), Some(&"start"));
+    let start = events.first().expect("start event");
+    assert_eq!(start.pointer("/schemaVersion"), Some(&json!(1)));
+    assert_eq!(start.pointer("/pullRequest/number"), Some(&json!(42)));
+    assert_eq!(types.last(), Some(&"end"));
+    assert_eq!(types.get(types.len() - 2), Some(&"summary"));
+    let end = events.last().expect("end event");
+    assert_eq!(end.pointer("/threads"), Some(&json!(threads)));
+
+    shutdown.shutdown().await;
+}
+
+#[tokio::test]
+async fn pr_ndjson_threads_carry_local_status() {
+    let (addr, handler, shutdown) = start_mitm().await.expect("start server");
+    let threads_body = include_str!("fixtures/review_threads_multiple_files.json").to_string();
+    let reviews_body = include_str!("fixtures/reviews_empty.json").to_string();
+    let mut responses = vec![reviews_body, threads_body].into_iter();
+    *handler.lock().expect("lock handler") = Box::new(move |_req| {
+        let body = responses.next().expect("response");
+        Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "application/json")
+            .body(Full::from(body))
+            .expect("build response")
+    });
+
+    let stdout = tokio::task::spawn_blocking(move || {
+        let mut cmd = vk_cmd(addr);
+        cmd.args([
+            "pr",
+            "https://github.com/leynos/shared-actions/pull/42",
+            "--format",
+            "ndjson",
+            "--local-status",
+        ]);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        String::from_utf8(output).expect("utf8")
+    })
+    .await
+    .expect("spawn blocking");
+
+    let threads: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("parse event line"))
+        .filter(|e| e.get("type") == Some(&json!("thread")))
+        .collect();
+    assert!(!threads.is_empty(), "expected thread events: {stdout}");
+    for thread in &threads {
+        assert!(
+            thread.get("localStatus").is_some(),
+            "unclassified: {thread}"
+        );
+    }
+
+    shutdown.shutdown().await;
+}
+
 /// Confirm banners and comment text are present in the output.
 fn validate_banner_content(output: &str) {
     assert!(
@@ -237,7 +446,7 @@ async fn pr_summarises_multiple_files() {
 async fn pr_renders_coderabbit_comment_without_extra_spacing() {
     let (addr, _handler, shutdown) = setup_mock_server_for_coderabbit_test().await;
 
-    let stdout = run_cli_and_capture_output(addr).await;
+    let stdout = run_cli_and_capture_output(addr, &["--format", "text"]).await;
     let plain = extract_coderabbit_comment_section(&stdout);
 
     assert_no_triple_newlines(&plain);
@@ -247,6 +456,82 @@ async fn pr_renders_coderabbit_comment_without_extra_spacing() {
     shutdown.shutdown().await;
 }
 
+#[tokio::test]
+async fn pr_defaults_to_markdown_when_piped() {
+    let (addr, _handler, shutdown) = setup_mock_server_for_coderabbit_test().await;
+
+    let stdout = run_cli_and_capture_output(addr, &[]).await;
+
+    assert!(!stdout.contains('\u{1b}'), "unexpected escape: {stdout}");
+    assert!(stdout.contains("========== code review =========="));
+    assert!(stdout.contains("\n## `"), "missing file heading: {stdout}");
+    assert!(
+        stdout.contains("\n```diff\n"),
+        "missing diff fence: {stdout}"
+    );
+    assert!(stdout.contains("[Permalink](https://"));
+    assert!(stdout.contains("**coderabbitai** wrote:"));
+    assert!(!stdout.contains(ICON_PERMALINK));
+    shutdown.shutdown().await;
+}
+
+#[rstest]
+#[case::forced(&["--ascii"], "C.UTF-8")]
+#[case::locale(&[], "C")]
+#[tokio::test]
+async fn pr_falls_back_to_ascii(#[case] flags: &'static [&'static str], #[case] locale: &str) {
+    let (addr, _handler, shutdown) = setup_mock_server_for_coderabbit_test().await;
+    let locale = locale.to_owned();
+    let stdout = tokio::task::spawn_blocking(move || {
+        let mut cmd = vk_cmd(addr);
+        cmd.env("LC_ALL", locale).args(flags).args([
+            "pr",
+            "https://github.com/leynos/netsuke/pull/177",
+            "--format",
+            "text",
+        ]);
+        let output = cmd.output().expect("run command");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).expect("utf8")
+    })
+    .await
+    .expect("spawn blocking");
+
+    assert!(stdout.contains("[link] https://"), "missing link: {stdout}");
+    assert!(stdout.contains("[file] "), "missing file: {stdout}");
+    assert!(stdout.contains("[comment]  "), "missing banner: {stdout}");
+    for icon in [ICON_PERMALINK, ICON_FILE, ICON_COMMENT] {
+        assert!(!stdout.contains(icon), "unexpected {icon}: {stdout}");
+    }
+    shutdown.shutdown().await;
+}
+
+#[rstest]
+#[case::text("text", "using ASCII output")]
+#[case::json("json", "emojis may not render correctly")]
+#[tokio::test]
+async fn pr_locale_warning_matches_format(#[case] format: &str, #[case] expected: &str) {
+    let (addr, _handler, shutdown) = setup_mock_server_for_coderabbit_test().await;
+    let format = format.to_owned();
+    let stderr = tokio::task::spawn_blocking(move || {
+        let mut cmd = vk_cmd(addr);
+        cmd.env("LC_ALL", "C").env("RUST_LOG", "warn").args([
+            "pr",
+            "https://github.com/leynos/netsuke/pull/177",
+            "--format",
+            &format,
+        ]);
+        let output = cmd.output().expect("run command");
+        assert!(output.status.success());
+        String::from_utf8(output.stderr).expect("utf8")
+    })
+    .await
+    .expect("spawn blocking");
+
+    assert!(stderr.contains(expected), "missing warning: {stderr}");
+    shutdown.shutdown().await;
+}
+
 async fn setup_mock_server_for_coderabbit_test()
 -> (SocketAddr, Arc<Mutex<RequestHandler>>, MitmShutdown) {
     let (addr, handler, shutdown) = start_mitm().await.expect("start server");
@@ -266,10 +551,14 @@ async fn setup_mock_server_for_coderabbit_test()
     (addr, handler, shutdown)
 }
 
-async fn run_cli_and_capture_output(addr: SocketAddr) -> String {
+async fn run_cli_and_capture_output(
+    addr: SocketAddr,
+    extra_args: &'static [&'static str],
+) -> String {
     tokio::task::spawn_blocking(move || {
         let mut cmd = vk_cmd(addr);
         cmd.args(["pr", "https://github.com/leynos/netsuke/pull/177"]);
+        cmd.args(extra_args);
         let output = cmd.output().expect("run command");
         assert!(
             output.status.success(),
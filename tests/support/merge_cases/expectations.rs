@@ -3,6 +3,10 @@ use super::data::MergeScenario;
 use vk::cli_args::{IssueArgs, PrArgs, ResolveArgs};
 
 #[derive(Clone, Debug)]
+#[expect(
+    clippy::large_enum_variant,
+    reason = "a handful of test cases; boxing would obscure the expectations"
+)]
 pub enum MergeExpectation {
     Pr {
         cli: PrArgs,
@@ -86,6 +90,7 @@ fn build_pr_args(reference: Option<&str>, files: &[&str], show_outdated: bool) -
         reference: reference.map(str::to_owned),
         files: files.iter().copied().map(str::to_owned).collect(),
         show_outdated,
+        ..PrArgs::default()
     }
 }
 
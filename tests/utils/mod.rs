@@ -188,7 +188,8 @@ pub fn vk_cmd(addr: SocketAddr) -> Command {
         .env("GITHUB_API_URL", format!("http://{addr}"))
         .env("GITHUB_TOKEN", "dummy")
         .env("NO_COLOR", "1")
-        .env("CLICOLOR_FORCE", "0");
+        .env("CLICOLOR_FORCE", "0")
+        .env("LC_ALL", "C.UTF-8");
     cmd
 }
 